@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+
+pub struct Vault {
+    deposits: BTreeMap<String, u128>,
+    unlock_at: u64,
+}
+
+impl Vault {
+    pub fn deposit(&mut self, who: String, amount: u128) {
+        let balance = self.deposits.entry(who).or_insert(0);
+        *balance += amount;
+    }
+
+    pub fn withdraw(&mut self, who: &str, now: u64) -> Option<u128> {
+        if now == self.unlock_at {
+            return self.deposits.remove(who);
+        }
+        None
+    }
+
+    pub fn shrink(&self, delta: i64) -> u64 {
+        delta as u64
+    }
+
+    pub fn largest(&self) -> u128 {
+        let amounts: Vec<u128> = self.deposits.values().copied().collect();
+        *amounts.last().unwrap()
+    }
+}
@@ -0,0 +1,305 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::findings::{assign_fingerprints, Finding, Severity};
+use crate::sarif;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaselineFormat {
+    Json,
+    Sarif,
+}
+
+impl BaselineFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            "sarif" => Some(Self::Sarif),
+            _ => None,
+        }
+    }
+}
+
+// Reads the fingerprints of every finding recorded in a previous report.
+// `renames` maps rule ids the report may still use to their current ids, so
+// findings of a renamed rule keep matching.
+pub fn load_baseline(
+    path: &Path,
+    format: BaselineFormat,
+    renames: &HashMap<String, String>,
+) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let document: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    let fingerprints = match format {
+        BaselineFormat::Json => native_fingerprints(&document, renames),
+        BaselineFormat::Sarif => sarif_fingerprints(&document, renames)?,
+    };
+
+    Ok(fingerprints)
+}
+
+// Like `load_baseline`, for the JSON a `--dir` scan writes. Each file's
+// fingerprints are kept apart, by path, so a finding only matches the file it
+// was recorded in.
+pub fn load_dir_baseline(
+    path: &Path,
+    renames: &HashMap<String, String>,
+) -> Result<HashMap<String, HashSet<String>>, Box<dyn std::error::Error>> {
+    let document: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let entries = document.as_array().ok_or("baseline is not the output of a --dir scan")?;
+
+    let mut baselines = HashMap::new();
+    for entry in entries {
+        let file = entry
+            .get("path")
+            .and_then(Value::as_str)
+            .ok_or("baseline entry has no `path`")?;
+        let report = entry.get("report").ok_or("baseline entry has no `report`")?;
+        baselines.insert(file.to_string(), native_fingerprints(report, renames));
+    }
+    Ok(baselines)
+}
+
+fn native_fingerprints(document: &Value, renames: &HashMap<String, String>) -> HashSet<String> {
+    let mut fingerprints = HashSet::new();
+    let mut renamed = Vec::new();
+    collect_native(document, renames, &mut fingerprints, &mut renamed);
+    fingerprints.extend(renamed_fingerprints(renamed));
+    fingerprints
+}
+
+// A finding of a renamed rule, under its current id, whose fingerprint has to be
+// recomputed. Reports list each finding twice, in its category and in
+// `findings`, so these are deduplicated on everything including the old fingerprint.
+#[derive(PartialEq, Eq, Hash)]
+struct RenamedFinding {
+    rule_id: String,
+    item: Option<String>,
+    message: String,
+    lines: (usize, usize),
+    old_fingerprint: Option<String>,
+}
+
+// Native reports nest findings under per-category arrays, so pick up every
+// `fingerprint` wherever it appears rather than depending on the layout.
+fn collect_native(
+    value: &Value,
+    renames: &HashMap<String, String>,
+    fingerprints: &mut HashSet<String>,
+    renamed: &mut Vec<RenamedFinding>,
+) {
+    match value {
+        Value::Object(map) => {
+            let rule_id = map.get("rule_id").and_then(Value::as_str);
+            let message = map.get("message").and_then(Value::as_str);
+            if let (Some(new_id), Some(message)) = (rule_id.and_then(|id| renames.get(id)), message)
+            {
+                let line = |key: &str| map.get(key).and_then(Value::as_u64).unwrap_or(0) as usize;
+                renamed.push(RenamedFinding {
+                    rule_id: new_id.clone(),
+                    item: map.get("item").and_then(Value::as_str).map(str::to_string),
+                    message: message.to_string(),
+                    lines: (line("line_start"), line("line_end")),
+                    old_fingerprint: map
+                        .get("fingerprint")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                });
+            }
+            for (key, child) in map {
+                match (key.as_str(), child) {
+                    ("fingerprint", Value::String(fingerprint)) => {
+                        fingerprints.insert(fingerprint.clone());
+                    }
+                    _ => collect_native(child, renames, fingerprints, renamed),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_native(item, renames, fingerprints, renamed);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Fingerprints the renamed findings would have had under their current ids,
+// numbered the way a scan numbers them
+fn renamed_fingerprints(renamed: Vec<RenamedFinding>) -> Vec<String> {
+    let unique: HashSet<RenamedFinding> = renamed.into_iter().collect();
+    let mut findings: Vec<Finding> = unique
+        .into_iter()
+        .map(|entry| {
+            let mut finding = Finding::new(
+                &entry.rule_id,
+                entry.message,
+                Severity::Info,
+                entry.lines.0,
+                entry.lines.1,
+            );
+            finding.item = entry.item;
+            finding
+        })
+        .collect();
+    // The set's order is arbitrary, and equal lines must number the same every time
+    findings.sort_by(|a, b| (&a.item, &a.message).cmp(&(&b.item, &b.message)));
+    assign_fingerprints(&mut findings);
+    findings.into_iter().map(|finding| finding.fingerprint).collect()
+}
+
+fn sarif_fingerprints(
+    document: &Value,
+    renames: &HashMap<String, String>,
+) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let runs = document
+        .get("runs")
+        .and_then(Value::as_array)
+        .ok_or("SARIF baseline has no `runs` array")?;
+
+    let mut fingerprints = HashSet::new();
+    let mut renamed_findings = Vec::new();
+    for result in runs
+        .iter()
+        .filter_map(|run| run.get("results").and_then(Value::as_array))
+        .flatten()
+    {
+        let original_id = result.get("ruleId").and_then(Value::as_str);
+        let recorded = result
+            .get("partialFingerprints")
+            .and_then(|partial| partial.get(sarif::FINGERPRINT_KEY))
+            .and_then(Value::as_str);
+        // A renamed rule's recorded fingerprint is under its old id, so it's recomputed
+        let renamed = original_id.is_some_and(|id| renames.contains_key(id));
+        if let (Some(recorded), false) = (recorded, renamed) {
+            fingerprints.insert(recorded.to_string());
+            continue;
+        }
+        let rule_id = original_id.map(|id| renames.get(id).map_or(id, String::as_str));
+        let message = result
+            .get("message")
+            .and_then(|message| message.get("text"))
+            .and_then(Value::as_str);
+        let region = result.pointer("/locations/0/physicalLocation/region");
+        let line = |key: &str| {
+            region.and_then(|region| region.get(key)).and_then(Value::as_u64).unwrap_or(0) as usize
+        };
+        let item = result
+            .pointer("/locations/0/logicalLocations/0/fullyQualifiedName")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        if let (Some(rule_id), Some(message)) = (rule_id, message) {
+            renamed_findings.push(RenamedFinding {
+                rule_id: rule_id.to_string(),
+                item,
+                message: message.to_string(),
+                lines: (line("startLine"), line("endLine")),
+                old_fingerprint: recorded.map(str::to_string),
+            });
+        }
+    }
+    fingerprints.extend(renamed_fingerprints(renamed_findings));
+
+    Ok(fingerprints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_rust_source, ParseResult};
+
+    const BEFORE: &str = "fn a(o: Option<u8>) { o.unwrap(); }\n";
+
+    fn scan(source: &str) -> ParseResult {
+        parse_rust_source(source, "test.rs")
+    }
+
+    fn write_baseline(contents: String) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), contents).unwrap();
+        file
+    }
+
+    fn remaining(source: &str, baseline: &Path, format: BaselineFormat) -> Vec<Option<String>> {
+        let fingerprints = load_baseline(baseline, format, &HashMap::new()).unwrap();
+        let mut result = scan(source);
+        result.apply_baseline(&fingerprints);
+        result.findings().map(|finding| finding.item.clone()).collect()
+    }
+
+    #[test]
+    fn sarif_baseline_suppresses_recorded_findings() {
+        let log = sarif::report(&scan(BEFORE), "test.rs");
+        let baseline = write_baseline(serde_json::to_string(&log).unwrap());
+
+        let after = format!("// moved down\n{}fn b(p: Option<u8>) {{ p.unwrap(); }}\n", BEFORE);
+        let left = remaining(&after, baseline.path(), BaselineFormat::Sarif);
+        assert_eq!(left, [Some("b".to_string())]);
+    }
+
+    #[test]
+    fn sarif_baseline_without_runs_is_an_error() {
+        let baseline = write_baseline("{}".to_string());
+        let error =
+            load_baseline(baseline.path(), BaselineFormat::Sarif, &HashMap::new()).unwrap_err();
+        assert_eq!(error.to_string(), "SARIF baseline has no `runs` array");
+    }
+
+    #[test]
+    fn identical_finding_in_another_function_is_not_suppressed() {
+        let baseline = write_baseline(serde_json::to_string(&scan(BEFORE)).unwrap());
+
+        let after = format!("{}fn b(o: Option<u8>) {{ o.unwrap(); }}\n", BEFORE);
+        let left = remaining(&after, baseline.path(), BaselineFormat::Json);
+        assert_eq!(left, [Some("b".to_string())]);
+    }
+
+    #[test]
+    fn repeated_finding_in_the_same_function_is_not_suppressed() {
+        let baseline = write_baseline(serde_json::to_string(&scan(BEFORE)).unwrap());
+
+        let after = "fn a(o: Option<u8>) { o.unwrap(); o.unwrap(); }\n";
+        assert_eq!(remaining(after, baseline.path(), BaselineFormat::Json).len(), 1);
+    }
+
+    #[test]
+    fn baseline_under_an_old_rule_id_matches_the_renamed_rule() {
+        // A report from before `panic-unwrap` was renamed from `unwrap-used`
+        let mut old = scan(BEFORE);
+        for finding in old.findings_mut() {
+            finding.rule_id = "unwrap-used".to_string();
+        }
+        old.assign_fingerprints();
+        old.collect_findings();
+        let baseline = write_baseline(serde_json::to_string(&old).unwrap());
+
+        assert_eq!(remaining(BEFORE, baseline.path(), BaselineFormat::Json).len(), 1);
+        let renames = HashMap::from([("unwrap-used".to_string(), "panic-unwrap".to_string())]);
+        let fingerprints = load_baseline(baseline.path(), BaselineFormat::Json, &renames).unwrap();
+        let mut result = scan(BEFORE);
+        result.apply_baseline(&fingerprints);
+        assert_eq!(result.findings().count(), 0);
+        assert_eq!(result.baseline_suppressed, 1);
+    }
+
+    #[test]
+    fn sarif_baseline_under_an_old_rule_id_matches_the_renamed_rule() {
+        let mut old = scan(BEFORE);
+        for finding in old.findings_mut() {
+            finding.rule_id = "unwrap-used".to_string();
+        }
+        old.assign_fingerprints();
+        old.collect_findings();
+        let log = sarif::report(&old, "test.rs");
+        let baseline = write_baseline(serde_json::to_string(&log).unwrap());
+
+        let renames = HashMap::from([("unwrap-used".to_string(), "panic-unwrap".to_string())]);
+        let fingerprints = load_baseline(baseline.path(), BaselineFormat::Sarif, &renames).unwrap();
+        let mut result = scan(BEFORE);
+        result.apply_baseline(&fingerprints);
+        assert_eq!(result.findings().count(), 0);
+    }
+}
@@ -0,0 +1,63 @@
+// Repeatedly scans a corpus and reports timing statistics as JSON, so
+// performance regressions show up when runs are compared.
+
+use serde::Serialize;
+use std::time::Instant;
+
+use crate::scanner::Scanner;
+
+// Contracts compiled into the binary so `bench` needs no checkout
+pub const CORPUS: &[(&str, &str)] = &[
+    ("flipper.rs", include_str!("../corpus/flipper.rs")),
+    ("vault.rs", include_str!("../corpus/vault.rs")),
+    ("vulnerable_token.rs", include_str!("../corpus/vulnerable_token.rs")),
+];
+
+// Durations of one full pass over the corpus, in milliseconds
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub files: usize,
+    pub iterations: usize,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+}
+
+pub fn run(scanner: &Scanner, corpus: &[(String, String)], iterations: usize) -> BenchReport {
+    let mut samples: Vec<f64> = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            for (_, source) in corpus {
+                std::hint::black_box(scanner.scan_source(source));
+            }
+            start.elapsed().as_secs_f64() * 1000.0
+        })
+        .collect();
+    samples.sort_by(f64::total_cmp);
+
+    // Nearest-rank percentile over the sorted samples
+    let percentile = |p: f64| {
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+        samples[rank.clamp(1, samples.len()) - 1]
+    };
+    let mean = if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().sum::<f64>() / samples.len() as f64
+    };
+
+    BenchReport {
+        files: corpus.len(),
+        iterations,
+        mean_ms: mean,
+        median_ms: percentile(50.0),
+        p95_ms: percentile(95.0),
+        min_ms: samples.first().copied().unwrap_or(0.0),
+        max_ms: samples.last().copied().unwrap_or(0.0),
+    }
+}
@@ -0,0 +1,147 @@
+// Per-function analysis results kept on disk between runs, so editing one
+// function of a large contract only re-analyzes that function. Entries are keyed
+// by a hash of the function's tokens plus everything else its findings depend on.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::findings::Finding;
+use crate::{CallEdge, MappingKey, ParsedUnsafeBlock, TaintEdge};
+
+// Line ranges here are relative to the first line of the function, so a cached
+// entry stays valid when code above the function moves it up or down.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct FnAnalysis {
+    // Findings raised inside the body, with the category list each belongs to
+    pub(crate) findings: Vec<(String, Finding)>,
+    // Large literals seen in the body, for the file-wide duplicate check
+    pub(crate) literals: Vec<(String, (usize, usize))>,
+    // Storage fields the body assigned `caller()` to
+    pub(crate) caller_writes: Vec<(String, (usize, usize))>,
+    // Storage fields the body compared against `caller()`
+    pub(crate) caller_comparisons: Vec<String>,
+    // Storage fields the body assigned an account parameter to
+    pub(crate) input_account_writes: Vec<(String, (usize, usize))>,
+    // Storage fields the body called as a cross-contract callee
+    pub(crate) callee_fields: Vec<String>,
+    // Crates the body used through fully qualified paths, once per use
+    pub(crate) qualified_roots: Vec<String>,
+    // Every `self.field` in the body, and which of them were writes
+    pub(crate) field_accesses: Vec<String>,
+    pub(crate) field_writes: Vec<(String, (usize, usize))>,
+    pub(crate) unsafe_blocks: Vec<ParsedUnsafeBlock>,
+    pub(crate) taint: Vec<TaintEdge>,
+    pub(crate) call_edges: Vec<CallEdge>,
+    // Tuple keys the body passed to methods of `self` fields
+    pub(crate) mapping_keys: Vec<MappingKey>,
+    // Whether the body calls `emit_event`
+    pub(crate) emits_event: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct FnCache {
+    dir: PathBuf,
+}
+
+impl FnCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub(crate) fn key(parts: &[&str]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        for part in parts {
+            hasher.update(b"\0");
+            hasher.update(part.as_bytes());
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    // A missing or unreadable entry is just a miss
+    pub(crate) fn load(&self, key: &str) -> Option<FnAnalysis> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    // Best effort: failing to write the cache never fails the scan
+    pub(crate) fn store(&self, key: &str, analysis: &FnAnalysis) {
+        if fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        // Scans run in parallel, so write aside and rename into place so that a
+        // reader never sees a half-written entry
+        let Ok(contents) = serde_json::to_string(analysis) else {
+            return;
+        };
+        let temporary = self.dir.join(format!(
+            "{}.{}-{:?}.tmp",
+            key,
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        if fs::write(&temporary, contents).is_err() {
+            return;
+        }
+        if fs::rename(&temporary, self.path(key)).is_err() {
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scanner::Scanner;
+
+    const TWO_FNS: &str = "fn a(v: &[u8]) -> u8 {\n    *v.first().unwrap()\n}\n\
+                           fn b(x: i64) -> u64 {\n    x as u64\n}\n";
+
+    fn scan(scanner: &Scanner, source: &str) -> (usize, Vec<(String, usize)>) {
+        let report = scanner.scan_source(source);
+        let found = report
+            .findings()
+            .map(|finding| (finding.rule_id.clone(), finding.line_start))
+            .collect();
+        (report.functions_from_cache, found)
+    }
+
+    #[test]
+    fn only_the_edited_function_is_analyzed_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = Scanner::builder().cache_dir(dir.path()).build();
+
+        assert_eq!(scan(&cached, TWO_FNS).0, 0);
+        assert_eq!(scan(&cached, TWO_FNS).0, 2);
+
+        let edited = TWO_FNS.replace("x as u64", "(x + 1) as u64");
+        let (from_cache, found) = scan(&cached, &edited);
+        assert_eq!(from_cache, 1);
+        assert_eq!(found, scan(&Scanner::builder().build(), &edited).1);
+    }
+
+    #[test]
+    fn replayed_findings_follow_the_function_and_its_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = Scanner::builder().cache_dir(dir.path()).build();
+        let uncached = Scanner::builder().build();
+        scan(&cached, TWO_FNS);
+
+        // Moving whole functions down reuses their entries at the new lines
+        let shifted = format!("\n\n{}", TWO_FNS);
+        assert_eq!(scan(&cached, &shifted), (2, scan(&uncached, &shifted).1));
+
+        // Blank lines inside `a` change where its finding is, so it's analyzed again
+        let spread = TWO_FNS.replace("{\n    *v.first()", "{\n\n\n\n    *v.first()");
+        assert_eq!(scan(&cached, &spread), (1, scan(&uncached, &spread).1));
+    }
+}
@@ -0,0 +1,324 @@
+// The `quard.toml` project configuration and its validation. Validation keeps
+// every value's position so problems are reported against the line they're on.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+use crate::findings::{Confidence, Severity};
+use crate::rules;
+
+// Looked for in the working directory when no `--config` is given, in this order
+pub const CONFIG_FILES: &[&str] = &["quard.toml", ".quard.toml"];
+
+const PROFILES: &[&str] = &["ink", "cosmwasm", "anchor", "near", "generic"];
+const CONFIDENCES: &[&str] = &["low", "medium", "high"];
+const SEVERITIES: &[&str] = &["info", "low", "medium", "high", "critical"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    profile: Option<Spanned<String>>,
+    min_confidence: Option<Spanned<String>>,
+    #[serde(default)]
+    enable: Vec<Spanned<String>>,
+    #[serde(default)]
+    disable: Vec<Spanned<String>>,
+    // Paths to skip when scanning a directory
+    #[serde(default)]
+    exclude: Vec<Spanned<String>>,
+    // Rule id to the severity its findings are reported with
+    #[serde(default)]
+    severity: BTreeMap<Spanned<String>, Spanned<String>>,
+    #[serde(default)]
+    suppress: Vec<RawSuppression>,
+    // Rule id to `true`/`false`, or to `{ enabled = .., severity = .. }`
+    #[serde(default)]
+    rules: BTreeMap<Spanned<String>, RawRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawRule {
+    Enabled(bool),
+    Settings(RawRuleSettings),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRuleSettings {
+    enabled: Option<bool>,
+    severity: Option<String>,
+}
+
+// The parts of a validated config the scanner applies
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub profile: Option<String>,
+    pub min_confidence: Option<Confidence>,
+    pub enable: Vec<String>,
+    pub disable: Vec<String>,
+    pub severity: BTreeMap<String, Severity>,
+    pub exclude: Vec<String>,
+    pub suppress: Vec<Suppression>,
+}
+
+// A `[[suppress]]` entry: drops `rule`'s findings, only in files matching `path` if given
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub rule: String,
+    pub path: Option<String>,
+}
+
+// The config file in `dir`, if there is one
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES.iter().map(|name| dir.join(name)).find(|path| path.is_file())
+}
+
+// Parses and validates a config; `[rules]` entries set to `false` become
+// disabled rules and their `severity` an override
+pub fn load(source: &str) -> Result<Config, Vec<ConfigError>> {
+    let errors = validate(source);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let raw: RawConfig = toml::from_str(source).map_err(|e| {
+        vec![ConfigError {
+            line: 1,
+            message: e.message().to_string(),
+        }]
+    })?;
+
+    let mut config = Config {
+        profile: raw.profile.map(Spanned::into_inner),
+        min_confidence: raw
+            .min_confidence
+            .and_then(|confidence| Confidence::from_name(confidence.get_ref())),
+        enable: raw.enable.into_iter().map(Spanned::into_inner).collect(),
+        disable: raw.disable.into_iter().map(Spanned::into_inner).collect(),
+        severity: raw
+            .severity
+            .into_iter()
+            .filter_map(|(id, severity)| {
+                Some((id.into_inner(), Severity::from_name(severity.get_ref())?))
+            })
+            .collect(),
+        exclude: raw.exclude.into_iter().map(Spanned::into_inner).collect(),
+        suppress: raw
+            .suppress
+            .into_iter()
+            .map(|suppression| Suppression {
+                rule: suppression.rule.into_inner(),
+                path: suppression.path.map(Spanned::into_inner),
+            })
+            .collect(),
+    };
+    for (id, rule) in raw.rules {
+        let id = id.into_inner();
+        let (enabled, severity) = match rule {
+            RawRule::Enabled(enabled) => (enabled, None),
+            RawRule::Settings(settings) => (settings.enabled.unwrap_or(true), settings.severity),
+        };
+        if !enabled {
+            config.disable.push(id.clone());
+        }
+        if let Some(severity) = severity.as_deref().and_then(Severity::from_name) {
+            config.severity.insert(id, severity);
+        }
+    }
+    Ok(config)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSuppression {
+    rule: Spanned<String>,
+    path: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// Every problem in a `.quard.toml`, in file order; empty when the config is valid
+pub fn validate(source: &str) -> Vec<ConfigError> {
+    let line_of = |offset: usize| source[..offset.min(source.len())].matches('\n').count() + 1;
+
+    let config: RawConfig = match toml::from_str(source) {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![ConfigError {
+                line: e.span().map_or(1, |span| line_of(span.start)),
+                message: e.message().to_string(),
+            }]
+        }
+    };
+
+    let mut errors = Vec::new();
+    let mut error = |value: &Spanned<String>, message: String| {
+        errors.push(ConfigError {
+            line: line_of(value.span().start),
+            message,
+        });
+    };
+    let mut check_choice = |value: &Spanned<String>, key: &str, choices: &[&str]| {
+        if !choices.contains(&value.get_ref().as_str()) {
+            error(
+                value,
+                format!(
+                    "`{}` must be one of {}, not `{}`",
+                    key,
+                    choices.join(", "),
+                    value.get_ref()
+                ),
+            );
+        }
+    };
+
+    if let Some(profile) = &config.profile {
+        check_choice(profile, "profile", PROFILES);
+    }
+    if let Some(confidence) = &config.min_confidence {
+        check_choice(confidence, "min_confidence", CONFIDENCES);
+    }
+    for severity in config.severity.values() {
+        check_choice(severity, "severity", SEVERITIES);
+    }
+    for (id, rule) in &config.rules {
+        if let RawRule::Settings(RawRuleSettings { severity: Some(severity), .. }) = rule {
+            if !SEVERITIES.contains(&severity.as_str()) {
+                error(
+                    id,
+                    format!(
+                        "`severity` of `{}` must be one of {}, not `{}`",
+                        id.get_ref(),
+                        SEVERITIES.join(", "),
+                        severity
+                    ),
+                );
+            }
+        }
+    }
+
+    let rule_ids = config
+        .enable
+        .iter()
+        .chain(&config.disable)
+        .chain(config.severity.keys())
+        .chain(config.rules.keys())
+        .chain(config.suppress.iter().map(|suppression| &suppression.rule));
+    for rule_id in rule_ids {
+        if rules::find(rule_id.get_ref()).is_none() {
+            error(rule_id, format!("unknown rule id `{}`", rule_id.get_ref()));
+        }
+    }
+
+    let globs = config
+        .exclude
+        .iter()
+        .chain(config.suppress.iter().filter_map(|suppression| suppression.path.as_ref()));
+    for glob in globs {
+        if let Err(problem) = check_glob(glob.get_ref()) {
+            error(glob, format!("invalid glob `{}`: {}", glob.get_ref(), problem));
+        }
+    }
+
+    errors.sort_by_key(|error| error.line);
+    errors
+}
+
+// Catches the mistakes glob matchers reject: unbalanced `[`/`{` and `**`
+// glued to other characters within a path component
+fn check_glob(glob: &str) -> Result<(), &'static str> {
+    if glob.is_empty() {
+        return Err("pattern is empty");
+    }
+
+    let mut brackets = 0usize;
+    let mut braces = 0usize;
+    for c in glob.chars() {
+        match c {
+            '[' => brackets += 1,
+            ']' => brackets = brackets.checked_sub(1).ok_or("unmatched `]`")?,
+            '{' => braces += 1,
+            '}' => braces = braces.checked_sub(1).ok_or("unmatched `}`")?,
+            _ => {}
+        }
+    }
+    if brackets > 0 {
+        return Err("unclosed `[`");
+    }
+    if braces > 0 {
+        return Err("unclosed `{`");
+    }
+
+    if glob
+        .split('/')
+        .any(|component| component.contains("**") && component != "**")
+    {
+        return Err("`**` must be a whole path component");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_rule_id_is_reported_on_its_line() {
+        let source = "profile = \"ink\"\ndisable = [\"sign-cast\", \"no-such-rule\"]\n";
+        let errors = validate(source);
+        assert_eq!(
+            errors,
+            [ConfigError {
+                line: 2,
+                message: "unknown rule id `no-such-rule`".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn bad_values_and_globs_are_all_reported() {
+        let errors = validate("profile = \"solana\"\nexclude = [\"src/[gen\"]\n");
+        let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, [1, 2]);
+    }
+
+    #[test]
+    fn valid_config_has_no_errors() {
+        let source = "min_confidence = \"high\"\n[severity]\nsign-cast = \"low\"\n";
+        assert!(validate(source).is_empty());
+    }
+
+    #[test]
+    fn exclude_and_suppress_entries_are_loaded() {
+        let source = "exclude = [\"tests/**\"]\n\n[[suppress]]\nrule = \"sign-cast\"\n\
+                      path = \"src/legacy/*.rs\"\n\n[[suppress]]\nrule = \"unsafe-block\"\n";
+        let config = load(source).unwrap();
+        assert_eq!(config.exclude, ["tests/**"]);
+        assert_eq!(
+            config.suppress,
+            [
+                Suppression {
+                    rule: "sign-cast".to_string(),
+                    path: Some("src/legacy/*.rs".to_string()),
+                },
+                Suppression {
+                    rule: "unsafe-block".to_string(),
+                    path: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// CSV export, one row per finding, for triaging in a spreadsheet. Quoting
+// follows RFC 4180 so multi-line messages stay in a single cell.
+
+use crate::ParseResult;
+
+const HEADER: &str = "file,rule_id,severity,line_start,line_end,message";
+
+// `reports` pairs each file, as the scan was given it, with its result
+pub fn render(reports: &[(&str, &ParseResult)]) -> String {
+    let mut out = format!("{}\r\n", HEADER);
+    for (file, result) in reports {
+        for finding in result.findings() {
+            let severity = format!("{:?}", finding.severity).to_lowercase();
+            out.push_str(&format!(
+                "{},{},{},{},{},{}\r\n",
+                field(file),
+                field(&finding.rule_id),
+                severity,
+                finding.line_start,
+                finding.line_end,
+                field(&finding.message)
+            ));
+        }
+    }
+    out
+}
+
+// Quoted only when needed, with embedded quotes doubled
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_row_per_finding_after_the_header() {
+        let result = crate::parse_rust_source("fn f(o: Option<u8>) -> u8 { o.unwrap() }\n", "a.rs");
+        let csv = render(&[("src/a.rs", &result)]);
+        let rows: Vec<&str> = csv.split_terminator("\r\n").collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], HEADER);
+        assert!(rows[1].starts_with("src/a.rs,panic-unwrap,medium,1,1,"), "{}", rows[1]);
+    }
+
+    #[test]
+    fn fields_are_quoted_only_when_needed() {
+        assert_eq!(field("plain"), "plain");
+        assert_eq!(field("a, b"), "\"a, b\"");
+        assert_eq!(field("say \"hi\"\nagain"), "\"say \"\"hi\"\"\nagain\"");
+    }
+}
@@ -0,0 +1,61 @@
+// `doctor`: which optional integrations this environment has, for working out
+// why line numbers are missing or a plugin won't load.
+
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+use syn::spanned::Spanned;
+
+use crate::plugin;
+
+#[derive(Debug, Serialize)]
+pub struct Diagnosis {
+    // `cargo expand`, for seeing what macros generate
+    pub cargo_expand: bool,
+    // `git` and the `tree-sitter` CLI on PATH
+    pub git: bool,
+    pub tree_sitter: bool,
+    // The ABI version plugins must be built for
+    pub plugin_abi_version: &'static str,
+    // proc-macro2's `span-locations`, without which every line is reported as 1
+    pub span_locations: bool,
+}
+
+pub fn diagnose() -> Diagnosis {
+    Diagnosis {
+        cargo_expand: runs("cargo", &["expand", "--version"]),
+        git: runs("git", &["--version"]),
+        tree_sitter: runs("tree-sitter", &["--version"]),
+        plugin_abi_version: plugin::ABI_VERSION,
+        span_locations: span_locations(),
+    }
+}
+
+// Whether `program` exists on PATH and exits successfully with `args`
+fn runs(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// Spans carry real positions only with the feature on; without it they all start at line 0
+fn span_locations() -> bool {
+    syn::parse_file("\nfn probe() {}")
+        .ok()
+        .and_then(|file| file.items.first().map(|item| item.span().start().line))
+        .is_some_and(|line| line == 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_program_does_not_run() {
+        assert!(!runs("quard-no-such-program", &["--version"]));
+    }
+}
@@ -0,0 +1,93 @@
+// Graphviz DOT call graph of a file, for rendering with `dot -Tsvg`. Functions
+// calling each other are solid edges; calls leaving the contract are dashed and
+// point at a box for the account or call they target.
+
+use std::collections::BTreeSet;
+
+use crate::ParseResult;
+
+pub fn render(result: &ParseResult) -> String {
+    let mut out = String::from("digraph calls {\n    rankdir=LR;\n    node [shape=ellipse];\n");
+
+    let mut functions: BTreeSet<&str> =
+        result.functions.iter().map(|function| function.name.as_str()).collect();
+    functions.extend(result.impl_blocks.iter().flat_map(|block| {
+        block.methods.iter().map(String::as_str)
+    }));
+    for function in &functions {
+        out.push_str(&format!("    {};\n", quote(function)));
+    }
+
+    let externals: BTreeSet<&str> = result
+        .call_edges
+        .iter()
+        .filter(|edge| edge.external)
+        .map(|edge| edge.callee.as_str())
+        .collect();
+    for target in &externals {
+        out.push_str(&format!(
+            "    {} [label={}, shape=box, style=dashed];\n",
+            quote(&external_node(target)),
+            quote(target)
+        ));
+    }
+
+    // One edge per pair, however many times the call is made
+    let edges: BTreeSet<(&str, &str, bool)> = result
+        .call_edges
+        .iter()
+        .map(|edge| (edge.caller.as_str(), edge.callee.as_str(), edge.external))
+        .collect();
+    for (caller, callee, external) in edges {
+        if external {
+            out.push_str(&format!(
+                "    {} -> {} [style=dashed];\n",
+                quote(caller),
+                quote(&external_node(callee))
+            ));
+        } else {
+            out.push_str(&format!("    {} -> {};\n", quote(caller), quote(callee)));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+// Kept apart from function ids, so a callee account named like a function stays its own node
+fn external_node(target: &str) -> String {
+    format!("external:{}", target)
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_external_call_is_a_dashed_edge() {
+        let source = include_str!("../../test_vulnerable_contract.rs");
+        let dot = render(&crate::parse_rust_source(source, "lib.rs"));
+        assert!(dot.starts_with("digraph calls {\n") && dot.ends_with("}\n"), "{}", dot);
+        assert!(dot.contains("    \"external:contract_addr\" [label=\"contract_addr\", shape=box"));
+        assert!(
+            dot.contains("    \"call_external\" -> \"external:contract_addr\" [style=dashed];\n"),
+            "{}",
+            dot
+        );
+    }
+
+    #[test]
+    fn internal_calls_are_solid_edges() {
+        let result = crate::parse_rust_source("fn a() { b(); }\nfn b() {}\n", "lib.rs");
+        assert!(render(&result).contains("    \"a\" -> \"b\";\n"));
+    }
+
+    #[test]
+    fn quotes_in_ids_are_escaped() {
+        assert_eq!(quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+}
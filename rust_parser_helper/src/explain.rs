@@ -0,0 +1,38 @@
+use crate::findings::Finding;
+use crate::rules::Rule;
+
+// Lines of source shown around the finding
+const CONTEXT_LINES: usize = 2;
+
+// Renders a single finding with its surrounding source, the rule's rationale
+// and how to fix it.
+pub fn explain_finding(finding: &Finding, rule: Option<&Rule>, source: &str) -> String {
+    let mut out = format!(
+        "{} [{}] {:?}\n{}\n\nLocation: lines {}-{}\n",
+        finding.rule_id,
+        finding.fingerprint,
+        finding.severity,
+        finding.message,
+        finding.line_start,
+        finding.line_end
+    );
+
+    let lines: Vec<&str> = source.lines().collect();
+    let first = finding.line_start.saturating_sub(CONTEXT_LINES).max(1);
+    let last = (finding.line_end + CONTEXT_LINES).min(lines.len());
+    for number in first..=last {
+        let marker = if (finding.line_start..=finding.line_end).contains(&number) {
+            '>'
+        } else {
+            ' '
+        };
+        out.push_str(&format!("{} {:>5} | {}\n", marker, number, lines[number - 1]));
+    }
+
+    if let Some(rule) = rule {
+        out.push_str(&format!("\nWhy it matters:\n  {}\n", rule.rationale));
+        out.push_str(&format!("\nSuggested fix:\n  {}\n", rule.remediation));
+    }
+
+    out
+}
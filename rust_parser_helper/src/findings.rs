@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "info" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    // One level more severe, saturating at `Critical`
+    pub fn escalated(self) -> Self {
+        match self {
+            Severity::Info => Severity::Low,
+            Severity::Low => Severity::Medium,
+            Severity::Medium => Severity::High,
+            Severity::High | Severity::Critical => Severity::Critical,
+        }
+    }
+}
+
+// How likely a rule's findings are to be real issues rather than heuristic noise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Confidence::Low),
+            "medium" => Some(Confidence::Medium),
+            "high" => Some(Confidence::High),
+            _ => None,
+        }
+    }
+}
+
+// How much work fixing a finding usually takes: a local edit, a change across
+// a function or two, or a redesign (often of storage or the message API)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Effort {
+    Trivial,
+    Moderate,
+    Significant,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule_id: String,
+    pub message: String,
+    pub severity: Severity,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub fingerprint: String,
+    // Path of the enclosing item, such as `token::Token::transfer`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    // Cargo feature that gates the code this finding is in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feature: Option<String>,
+    // Replacement for the offending expression, for editors offering quick fixes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggested_fix: Option<String>,
+    // From the rule's metadata; unknown for plugin rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<Effort>,
+}
+
+impl Finding {
+    // For rules outside this crate, such as plugins
+    pub fn new(
+        rule_id: &str,
+        message: String,
+        severity: Severity,
+        line_start: usize,
+        line_end: usize,
+    ) -> Self {
+        Self {
+            rule_id: rule_id.to_string(),
+            fingerprint: fingerprint(rule_id, None, &message, 0),
+            message,
+            severity,
+            line_start,
+            line_end,
+            item: None,
+            feature: None,
+            suggested_fix: None,
+            effort: None,
+        }
+    }
+
+    pub fn with_fix(mut self, fix: String) -> Self {
+        self.suggested_fix = Some(fix);
+        self
+    }
+}
+
+// Stable identity of a finding across runs: deliberately independent of line
+// numbers so unrelated edits above a finding don't change it. `occurrence`
+// tells apart findings with the same rule, item and message, counted in file order.
+pub fn fingerprint(rule_id: &str, item: Option<&str>, message: &str, occurrence: usize) -> String {
+    // FNV-1a, chosen over `DefaultHasher` because its output is fixed across Rust releases
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let occurrence = occurrence.to_string();
+    let parts = [rule_id, item.unwrap_or(""), message, &occurrence];
+    for byte in parts.iter().flat_map(|part| part.bytes().chain([0])) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// Sets the fingerprint of each of a file's findings, numbering the ones that
+// would otherwise collide by where they start
+pub fn assign_fingerprints<'a>(findings: impl IntoIterator<Item = &'a mut Finding>) {
+    let mut findings: Vec<&mut Finding> = findings.into_iter().collect();
+    findings.sort_by_key(|finding| (finding.line_start, finding.line_end));
+    let mut occurrences: HashMap<(String, Option<String>, String), usize> = HashMap::new();
+    for finding in findings {
+        let key = (finding.rule_id.clone(), finding.item.clone(), finding.message.clone());
+        let occurrence = occurrences.entry(key).or_default();
+        finding.fingerprint = fingerprint(
+            &finding.rule_id,
+            finding.item.as_deref(),
+            &finding.message,
+            *occurrence,
+        );
+        *occurrence += 1;
+    }
+}
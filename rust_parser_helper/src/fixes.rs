@@ -0,0 +1,61 @@
+// Minimal replacement snippets for findings with a mechanical fix
+
+use crate::tokens;
+
+// `x.unwrap()` -> `x?`, for callers that return `Option`/`Result`
+pub fn propagate_none(node: &syn::ExprMethodCall) -> String {
+    format!("{}?", tokens::snippet(&node.receiver))
+}
+
+// `a + b` -> `a.checked_add(b)`; `None` for operators without a checked form
+pub fn checked_arithmetic(node: &syn::ExprBinary) -> Option<String> {
+    let method = match node.op {
+        syn::BinOp::Add(_) | syn::BinOp::AddAssign(_) => "checked_add",
+        syn::BinOp::Sub(_) | syn::BinOp::SubAssign(_) => "checked_sub",
+        syn::BinOp::Mul(_) | syn::BinOp::MulAssign(_) => "checked_mul",
+        syn::BinOp::Div(_) | syn::BinOp::DivAssign(_) => "checked_div",
+        _ => return None,
+    };
+    let left = tokens::snippet(&node.left);
+    let receiver = match &*node.left {
+        syn::Expr::Path(_) | syn::Expr::Field(_) | syn::Expr::MethodCall(_) | syn::Expr::Lit(_) => {
+            left
+        }
+        _ => format!("({})", left),
+    };
+    Some(format!("{}.{}({})", receiver, method, tokens::snippet(&node.right)))
+}
+
+// `now == deadline` -> `now >= deadline`
+pub fn deadline_comparison(node: &syn::ExprBinary) -> String {
+    let operator = match node.op {
+        syn::BinOp::Ne(_) => "<",
+        _ => ">=",
+    };
+    format!(
+        "{} {} {}",
+        tokens::snippet(&node.left),
+        operator,
+        tokens::snippet(&node.right)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checked(expr: &str) -> Option<String> {
+        checked_arithmetic(&syn::parse_str(expr).unwrap())
+    }
+
+    #[test]
+    fn arithmetic_suggests_its_checked_method() {
+        assert_eq!(checked("a + b").as_deref(), Some("a.checked_add(b)"));
+        assert_eq!(
+            checked("self.total -= amount").as_deref(),
+            Some("self.total.checked_sub(amount)")
+        );
+        assert_eq!(checked("a * b + c").as_deref(), Some("(a * b).checked_add(c)"));
+        assert_eq!(checked("a % b"), None);
+    }
+}
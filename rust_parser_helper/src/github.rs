@@ -0,0 +1,56 @@
+// GitHub Actions workflow commands, which the runner turns into inline
+// annotations on the pull request without a SARIF upload step.
+
+use crate::findings::Severity;
+use crate::ParseResult;
+
+// One `::error`/`::warning`/`::notice` line per finding, attributed to `file`
+pub fn render(result: &ParseResult, file: &str) -> String {
+    let mut out = String::new();
+    for finding in result.findings() {
+        let command = match finding.severity {
+            Severity::Critical | Severity::High => "error",
+            Severity::Medium | Severity::Low => "warning",
+            Severity::Info => "notice",
+        };
+        out.push_str(&format!(
+            "::{} file={},line={},endLine={},title={}::{}\n",
+            command,
+            escape_property(file),
+            finding.line_start,
+            finding.line_end,
+            escape_property(&finding.rule_id),
+            escape_data(&finding.message)
+        ));
+    }
+    out
+}
+
+// Workflow commands end at a newline, so line breaks and `%` are percent-encoded
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+// Properties are additionally split on `,` and end at `::`
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_unsafe_block_is_an_error_annotation() {
+        let source = include_str!("../../test_vulnerable_contract.rs");
+        let output = render(&crate::parse_rust_source(source, "lib.rs"), "contracts/lib.rs");
+        let annotation = "::error file=contracts/lib.rs,line=44,endLine=48,title=unsafe-block::";
+        assert!(output.lines().any(|line| line.starts_with(annotation)), "{}", output);
+    }
+
+    #[test]
+    fn properties_and_messages_are_escaped() {
+        assert_eq!(escape_property("a:b,c"), "a%3Ab%2Cc");
+        assert_eq!(escape_data("50%\ndone"), "50%25%0Adone");
+    }
+}
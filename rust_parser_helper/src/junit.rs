@@ -0,0 +1,147 @@
+// JUnit XML for CI systems that render test reports. Each rule is a suite and
+// each finding a failed test case; files without findings get a passing case in
+// a suite of their own, so the report still shows they were scanned.
+
+use std::collections::BTreeMap;
+
+use crate::findings::Finding;
+use crate::ParseResult;
+
+// Suite holding the passing case of every file with no findings
+const CLEAN_SUITE: &str = "no-findings";
+
+// `reports` pairs each file, as the scan was given it, with its result
+pub fn render(reports: &[(&str, &ParseResult)]) -> String {
+    let mut by_rule: BTreeMap<&str, Vec<(&str, &Finding)>> = BTreeMap::new();
+    let mut clean = Vec::new();
+    for (file, result) in reports {
+        let mut any = false;
+        for finding in result.findings() {
+            by_rule.entry(&finding.rule_id).or_default().push((file, finding));
+            any = true;
+        }
+        if !any {
+            clean.push(*file);
+        }
+    }
+
+    let failures: usize = by_rule.values().map(Vec::len).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"rust_parser_helper\" tests=\"{}\" failures=\"{}\">\n",
+        failures + clean.len(),
+        failures
+    ));
+    for (rule, findings) in &by_rule {
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape(rule),
+            findings.len(),
+            findings.len()
+        ));
+        for (file, finding) in findings {
+            let location = format!("{}:{}-{}", file, finding.line_start, finding.line_end);
+            let severity = format!("{:?}", finding.severity).to_lowercase();
+            out.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n",
+                escape(file),
+                escape(&location)
+            ));
+            out.push_str(&format!(
+                "      <failure message=\"{}\" type=\"{}\">{}: {}</failure>\n",
+                escape(&finding.message),
+                severity,
+                escape(&location),
+                escape(&finding.message)
+            ));
+            out.push_str("    </testcase>\n");
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    if !clean.is_empty() {
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"0\">\n",
+            CLEAN_SUITE,
+            clean.len()
+        ));
+        for file in clean {
+            out.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"{}\"/>\n",
+                escape(file),
+                escape(file)
+            ));
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+// Safe in both attribute values and text, including line breaks in messages
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            // Other control characters can't appear in XML 1.0 at all
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_is_well_formed_xml() {
+        let source = include_str!("../../test_vulnerable_contract.rs");
+        let sample = crate::parse_rust_source(source, "lib.rs");
+        let clean = crate::parse_rust_source("fn f() {}\n", "clean.rs");
+        let xml = render(&[("lib.rs", &sample), ("clean.rs", &clean)]);
+        let document = roxmltree::Document::parse(&xml).unwrap();
+
+        let root = document.root_element();
+        assert_eq!(root.tag_name().name(), "testsuites");
+        let findings = sample.findings().count();
+        assert_eq!(root.attribute("failures"), Some(findings.to_string().as_str()));
+        assert_eq!(root.attribute("tests"), Some((findings + 1).to_string().as_str()));
+
+        let failures: Vec<roxmltree::Node> =
+            document.descendants().filter(|node| node.has_tag_name("failure")).collect();
+        assert_eq!(failures.len(), findings);
+
+        let suite = |name: &str| {
+            root.children().find(|node| node.attribute("name") == Some(name)).unwrap()
+        };
+        let case = suite("unsafe-block").first_element_child().unwrap();
+        assert_eq!(case.attribute("name"), Some("lib.rs:44-48"));
+        let failure = case.first_element_child().unwrap();
+        assert_eq!(failure.attribute("type"), Some("high"));
+
+        let cases: Vec<&str> =
+            suite(CLEAN_SUITE).children().filter_map(|case| case.attribute("classname")).collect();
+        assert_eq!(cases, ["clean.rs"]);
+    }
+
+    #[test]
+    fn markup_and_control_characters_in_messages_stay_well_formed() {
+        let mut result = crate::parse_rust_source("fn f(o: Option<u8>) { o.unwrap(); }\n", "a.rs");
+        let message = "<b>&\"quoted\"</b>\nline two\u{1}";
+        for finding in result.findings_mut() {
+            finding.message = message.to_string();
+        }
+        let xml = render(&[("a&b.rs", &result)]);
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let failure = document.descendants().find(|node| node.has_tag_name("failure")).unwrap();
+        assert_eq!(failure.attribute("message"), Some("<b>&\"quoted\"</b>\nline two"));
+    }
+}
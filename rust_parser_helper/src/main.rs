@@ -1,368 +1,735 @@
-use clap::{Arg, Command};
-use serde::{Deserialize, Serialize};
+use clap::{Arg, ArgAction, Command};
+use rust_parser_helper::baseline::{self, BaselineFormat};
+use rust_parser_helper::findings::Severity;
+use rust_parser_helper::plugin::Plugin;
+use rust_parser_helper::scanner::Scanner;
+use prost::Message;
+use rust_parser_helper::{
+    bench, config, csv, doctor, dot, explain, github, junit, manifest, markdown, merge, pretty,
+    proto, rules, sarif, table, ParseResult,
+};
+use serde::Serialize;
 use std::fs;
-use syn::{visit::Visit, ItemFn, ItemStruct, ItemImpl, ItemTrait, Attribute, Visibility};
-use syn::spanned::Spanned;
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedFunction {
-    name: String,
-    visibility: String,
-    parameters: Vec<ParsedParameter>,
-    return_type: Option<String>,
-    attributes: Vec<String>,
-    is_async: bool,
-    is_unsafe: bool,
-    line_start: usize,
-    line_end: usize,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedParameter {
-    name: String,
-    param_type: String,
-    is_mutable: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedStruct {
-    name: String,
-    visibility: String,
-    fields: Vec<ParsedField>,
-    attributes: Vec<String>,
-    line_start: usize,
-    line_end: usize,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedField {
-    name: String,
-    field_type: String,
-    visibility: String,
-}
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedTrait {
-    name: String,
-    visibility: String,
-    methods: Vec<String>,
-    attributes: Vec<String>,
-    line_start: usize,
-    line_end: usize,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedImpl {
-    target_type: String,
-    trait_name: Option<String>,
-    methods: Vec<String>,
-    line_start: usize,
-    line_end: usize,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParsedUnsafeBlock {
-    line_start: usize,
-    line_end: usize,
-    context: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct ParseResult {
-    functions: Vec<ParsedFunction>,
-    structs: Vec<ParsedStruct>,
-    traits: Vec<ParsedTrait>,
-    impl_blocks: Vec<ParsedImpl>,
-    unsafe_blocks: Vec<ParsedUnsafeBlock>,
-    attributes: Vec<String>,
-    uses: Vec<String>,
-    contract_type: String,
-    errors: Vec<String>,
-}
-
-struct RustVisitor {
-    result: ParseResult,
-}
-
-impl RustVisitor {
-    fn new(source: &str) -> Self {
-        let mut visitor = Self {
-            result: ParseResult {
-                functions: Vec::new(),
-                structs: Vec::new(),
-                traits: Vec::new(),
-                impl_blocks: Vec::new(),
-                unsafe_blocks: Vec::new(),
-                attributes: Vec::new(),
-                uses: Vec::new(),
-                contract_type: String::new(),
-                errors: Vec::new(),
-            },
-        };
-        visitor.detect_contract_type(source);
-        visitor
+fn main() {
+    let matches = Command::new("Rust Parser Helper")
+        .version("0.1.0")
+        .about("Parses Rust smart contracts using syn crate")
+        .after_help(
+            "Exit status: 0 when the scan completes, 1 on I/O errors, invalid arguments or \
+             config, and unparseable files under --fail-on-parse-error, 2 when a finding is at \
+             or above --fail-on. Output is always written before exiting with 2.",
+        )
+        .subcommand_negates_reqs(true)
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("bench")
+                .about("Time repeated scans of a corpus and print the statistics as JSON")
+                .arg(
+                    Arg::new("iterations")
+                        .long("iterations")
+                        .help("Number of passes over the corpus")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("10"),
+                )
+                .arg(
+                    Arg::new("corpus")
+                        .long("corpus")
+                        .help("Directory of .rs files to scan instead of the bundled corpus")
+                        .value_name("DIR"),
+                ),
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("Combine JSON reports from separate scans into one aggregated report")
+                .arg(
+                    Arg::new("reports")
+                        .help("JSON reports to merge, of single files or from --dir")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .help("Output file for the merged report")
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Report which optional integrations are available, as JSON booleans"),
+        )
+        .arg(
+            Arg::new("file")
+                .help("Rust file to parse")
+                .required_unless_present_any(["config-validate", "dir", "source"])
+                .index(1),
+        )
+        .arg(
+            Arg::new("source")
+                .long("source")
+                .help("Analyze this Rust source instead of reading a file")
+                .value_name("CODE")
+                .conflicts_with_all(["file", "dir"]),
+        )
+        .arg(
+            Arg::new("dir")
+                .long("dir")
+                .help(
+                    "Scan every .rs file under a directory, honoring .gitignore, as a JSON \
+                     array or, with --format markdown, csv or junit, a single report",
+                )
+                .value_name("DIR")
+                .conflicts_with_all([
+                    "file",
+                    "group-by",
+                    "explain-finding",
+                    "baseline-format",
+                    "detect-only",
+                    "severity-summary-only",
+                ]),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .help("Output file for the result")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("Output format")
+                .value_parser([
+                    "json", "protobuf", "table", "github", "sarif", "markdown", "csv", "dot",
+                    "junit", "pretty",
+                ])
+                .default_value("json"),
+        )
+        .arg(
+            Arg::new("indent")
+                .long("indent")
+                .help("Indent JSON output by N spaces, or by tabs with `tab`")
+                .value_name("N|tab")
+                .value_parser(parse_indent)
+                .default_value("2"),
+        )
+        .arg(
+            Arg::new("profile-from-manifest")
+                .long("profile-from-manifest")
+                .help("Select the rule profile from Cargo.toml dependencies instead of source heuristics")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("manifest")
+                .long("manifest")
+                .help("Cargo.toml to read (defaults to the nearest one above the file)")
+                .value_name("CARGO_TOML"),
+        )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .help("Previous report whose findings are suppressed from this run; with --dir, \
+                       the JSON of a previous --dir scan, matched file by file")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::new("baseline-format")
+                .long("baseline-format")
+                .help("Format of the baseline file")
+                .value_parser(["json", "sarif"])
+                .default_value("json"),
+        )
+        .arg(
+            Arg::new("baseline-update")
+                .long("baseline-update")
+                .help("Suppress the findings in FILE, then rewrite it with this run's findings \
+                       unless --fail-on failed the run")
+                .value_name("FILE")
+                .conflicts_with_all(["baseline", "baseline-format", "dir"]),
+        )
+        .arg(
+            Arg::new("fail-on")
+                .long("fail-on")
+                .help("Exit with status 2 if a reported finding is at least this severe")
+                .value_name("SEVERITY")
+                .value_parser(["off", "info", "low", "medium", "high", "critical"])
+                .default_value("off"),
+        )
+        .arg(
+            Arg::new("fail-on-parse-error")
+                .long("fail-on-parse-error")
+                .help("In --dir mode, exit with status 1 if any file failed to parse")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("rename-rule")
+                .long("rename-rule")
+                .help("Treat baseline findings of rule OLD as findings of NEW (repeatable)")
+                .value_name("OLD=NEW")
+                .value_parser(parse_rename)
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("explain-finding")
+                .long("explain-finding")
+                .help("Print the full context of the finding with this fingerprint")
+                .value_name("FINGERPRINT"),
+        )
+        .arg(
+            Arg::new("jobs")
+                .long("jobs")
+                .short('j')
+                .help("Parse at most N files at once in --dir mode")
+                .value_name("N")
+                .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..)),
+        )
+        .arg(
+            Arg::new("deny")
+                .long("deny")
+                .help("Don't run this rule (repeatable)")
+                .value_name("RULE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("only")
+                .long("only")
+                .help("Run only this rule (repeatable)")
+                .value_name("RULE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("features")
+                .long("features")
+                .help("Comma-separated cargo features to treat as active")
+                .value_name("FEATURES")
+                .value_delimiter(','),
+        )
+        .arg(
+            Arg::new("exclude-inactive-features")
+                .long("exclude-inactive-features")
+                .help("Drop findings in code gated behind features not passed to --features")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("max-findings")
+                .long("max-findings")
+                .help("Report at most N findings per file, keeping the most severe")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("max-params")
+                .long("max-params")
+                .help("Flag functions taking more than N parameters")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("7"),
+        )
+        .arg(
+            Arg::new("privileged-field")
+                .long("privileged-field")
+                .help("Also treat fields and helpers whose name contains NAME as access control \
+                       (repeatable; owner, admin, role... are built in)")
+                .value_name("NAME")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("plugin")
+                .long("plugin")
+                .help("Load custom rules from a plugin cdylib (repeatable)")
+                .value_name("PATH")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("detect-only")
+                .long("detect-only")
+                .help("Print only the detected contract type, skipping analysis")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("cache")
+                .long("cache")
+                .help("Directory for per-function results reused across runs")
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::new("group-by")
+                .long("group-by")
+                .help("Output only the findings, bucketed by this key (JSON only)")
+                .value_parser(["severity"]),
+        )
+        .arg(
+            Arg::new("severity-summary-only")
+                .long("severity-summary-only")
+                .help("Print only the number of findings per severity, as one JSON line")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["format", "group-by"]),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Project config to apply (defaults to quard.toml or .quard.toml in the \
+                       working directory)")
+                .value_name("PATH"),
+        )
+        .arg(
+            Arg::new("config-validate")
+                .long("config-validate")
+                .help("Check a .quard.toml for unknown rule ids, bad values and malformed globs")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .help("Width of table output, instead of the terminal's")
+                .value_name("COLUMNS")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .try_get_matches()
+        .unwrap_or_else(|e| {
+            // clap exits usage errors with 2, which is reserved for --fail-on
+            let _ = e.print();
+            std::process::exit(if e.use_stderr() { 1 } else { 0 });
+        });
+
+    match matches.subcommand() {
+        Some(("bench", bench_matches)) => return run_bench(bench_matches),
+        Some(("merge", merge_matches)) => return run_merge(merge_matches),
+        Some(("doctor", _)) => return run_doctor(),
+        _ => {}
     }
 
-    fn extract_attributes(attrs: &[Attribute]) -> Vec<String> {
-        attrs.iter()
-            .map(|attr| quote::quote!(#attr).to_string())
-            .collect()
+    if let Some(config_path) = matches.get_one::<String>("config-validate") {
+        return validate_config(config_path);
     }
 
-    fn visibility_to_string(vis: &Visibility) -> String {
-        match vis {
-            Visibility::Public(_) => "pub".to_string(),
-            Visibility::Restricted(_) => "pub(restricted)".to_string(),
-            Visibility::Inherited => "private".to_string(),
+    // Names the input in messages and in github/sarif locations
+    let file_path = matches
+        .get_one::<String>("file")
+        .or_else(|| matches.get_one::<String>("dir"))
+        .map_or("<source>", String::as_str);
+
+    let mut builder = Scanner::builder();
+    let config_path = matches
+        .get_one::<String>("config")
+        .map(PathBuf::from)
+        .or_else(|| config::find_config(Path::new(".")));
+    if let Some(config_path) = config_path {
+        let loaded = fs::read_to_string(&config_path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| {
+                config::load(&source).map_err(|errors| {
+                    errors.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+                })
+            });
+        match loaded {
+            Ok(config) => builder = builder.config(&config),
+            Err(e) => {
+                eprintln!("Error in config {}: {}", config_path.display(), e);
+                std::process::exit(1);
+            }
         }
     }
+    if matches.get_flag("profile-from-manifest") {
+        let manifest_path = matches
+            .get_one::<String>("manifest")
+            .map(PathBuf::from)
+            .or_else(|| manifest::find_manifest(Path::new(file_path)));
+
+        let Some(manifest_path) = manifest_path else {
+            eprintln!("Error: no Cargo.toml found for {}", file_path);
+            std::process::exit(1);
+        };
 
-    fn get_line_numbers(&self, _span: proc_macro2::Span) -> (usize, usize) {
-        // proc_macro2::Span doesn't provide line numbers in stable Rust
-        // Return default values for now
-        (1, 1)
+        match manifest::profile_from_manifest(&manifest_path) {
+            Ok(profile) => builder = builder.profile(profile),
+            Err(e) => {
+                eprintln!("Error reading manifest {}: {}", manifest_path.display(), e);
+                std::process::exit(1);
+            }
+        }
     }
-
-    fn detect_contract_type(&mut self, source: &str) {
-        if source.contains("#[ink::contract]") || source.contains("ink_lang") {
-            self.result.contract_type = "ink".to_string();
-        } else if source.contains("cosmwasm_std") || source.contains("InstantiateMsg") {
-            self.result.contract_type = "cosmwasm".to_string();
-        } else if source.contains("anchor_lang") || source.contains("#[program]") {
-            self.result.contract_type = "anchor".to_string();
-        } else if source.contains("near_sdk") || source.contains("#[near_bindgen]") {
-            self.result.contract_type = "near".to_string();
-        } else {
-            self.result.contract_type = "generic".to_string();
+    if let Some(manifest_path) = matches.get_one::<String>("manifest") {
+        match manifest::panic_strategy(Path::new(manifest_path)) {
+            Ok(strategy) => builder = builder.panic_strategy(strategy),
+            Err(e) => {
+                eprintln!("Error reading manifest {}: {}", manifest_path, e);
+                std::process::exit(1);
+            }
         }
     }
-}
-
-impl<'ast> Visit<'ast> for RustVisitor {
-    fn visit_item_fn(&mut self, node: &'ast ItemFn) {
-        let (line_start, line_end) = self.get_line_numbers(node.span());
-        
-        let parameters = node.sig.inputs.iter()
-            .filter_map(|arg| {
-                if let syn::FnArg::Typed(pat_type) = arg {
-                    if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
-                        return Some(ParsedParameter {
-                            name: pat_ident.ident.to_string(),
-                            param_type: quote::quote!(#pat_type.ty).to_string(),
-                            is_mutable: pat_ident.mutability.is_some(),
-                        });
-                    }
-                }
-                None
-            })
-            .collect();
-
-        let return_type = match &node.sig.output {
-            syn::ReturnType::Default => None,
-            syn::ReturnType::Type(_, ty) => Some(quote::quote!(#ty).to_string()),
-        };
+    for path in matches.get_many::<String>("plugin").into_iter().flatten() {
+        match Plugin::load(Path::new(path)) {
+            Ok(plugin) => builder = builder.plugin(plugin),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    for rule in matches.get_many::<String>("deny").into_iter().flatten() {
+        builder = builder.disable_rule(rule);
+    }
+    for rule in matches.get_many::<String>("only").into_iter().flatten() {
+        builder = builder.enable_rule(rule);
+    }
+    if let Some(features) = matches.get_many::<String>("features") {
+        builder = builder.features(features.cloned());
+    }
+    if let Some(dir) = matches.get_one::<String>("cache") {
+        builder = builder.cache_dir(dir);
+    }
+    if let Some(jobs) = matches.get_one::<usize>("jobs") {
+        builder = builder.jobs(*jobs);
+    }
+    if let Some(max) = matches.get_one::<usize>("max-params") {
+        builder = builder.max_params(*max);
+    }
+    for marker in matches.get_many::<String>("privileged-field").into_iter().flatten() {
+        builder = builder.privileged_marker(marker);
+    }
+    let scanner = builder
+        .exclude_inactive_features(matches.get_flag("exclude-inactive-features"))
+        .build();
 
-        let function = ParsedFunction {
-            name: node.sig.ident.to_string(),
-            visibility: Self::visibility_to_string(&node.vis),
-            parameters,
-            return_type,
-            attributes: Self::extract_attributes(&node.attrs),
-            is_async: node.sig.asyncness.is_some(),
-            is_unsafe: node.sig.unsafety.is_some(),
-            line_start,
-            line_end,
-        };
+    let indent = matches.get_one::<String>("indent").unwrap();
 
-        self.result.functions.push(function);
-        
-        // Continue visiting
-        syn::visit::visit_item_fn(self, node);
+    if let Some(dir) = matches.get_one::<String>("dir") {
+        return run_dir(&scanner, Path::new(dir), &matches);
     }
 
-    fn visit_item_struct(&mut self, node: &'ast ItemStruct) {
-        let (line_start, line_end) = self.get_line_numbers(node.span());
-        
-        let fields = match &node.fields {
-            syn::Fields::Named(fields) => {
-                fields.named.iter()
-                    .filter_map(|field| {
-                        field.ident.as_ref().map(|ident| ParsedField {
-                            name: ident.to_string(),
-                            field_type: quote::quote!(#field.ty).to_string(),
-                            visibility: Self::visibility_to_string(&field.vis),
-                        })
-                    })
-                    .collect()
+    let source = match matches.get_one::<String>("source") {
+        Some(source) => Ok(source.clone()),
+        None => fs::read_to_string(file_path),
+    };
+    match source {
+        Ok(source) => {
+            if matches.get_flag("detect-only") {
+                println!("{}", rust_parser_helper::detect_contract_type(&source));
+                return;
             }
-            syn::Fields::Unnamed(fields) => {
-                fields.unnamed.iter()
-                    .enumerate()
-                    .map(|(i, field)| ParsedField {
-                        name: format!("field_{}", i),
-                        field_type: quote::quote!(#field.ty).to_string(),
-                        visibility: Self::visibility_to_string(&field.vis),
-                    })
-                    .collect()
+
+            let mut result = scanner.scan_file(Path::new(file_path), &source);
+
+            if let Some(fingerprint) = matches.get_one::<String>("explain-finding") {
+                let Some(finding) = result.findings().find(|f| &f.fingerprint == fingerprint) else {
+                    eprintln!("Error: no finding with fingerprint {} in {}", fingerprint, file_path);
+                    std::process::exit(1);
+                };
+                let rule = rules::find(&finding.rule_id);
+                print!("{}", explain::explain_finding(finding, rule, &source));
+                return;
             }
-            syn::Fields::Unit => Vec::new(),
-        };
 
-        let struct_info = ParsedStruct {
-            name: node.ident.to_string(),
-            visibility: Self::visibility_to_string(&node.vis),
-            fields,
-            attributes: Self::extract_attributes(&node.attrs),
-            line_start,
-            line_end,
-        };
+            // The findings as they stand become the new baseline, so they're
+            // captured before the old baseline suppresses any of them
+            let baseline_update = matches.get_one::<String>("baseline-update");
+            let snapshot = baseline_update.map(|_| to_json(&result, indent));
+            let baseline_path = matches
+                .get_one::<String>("baseline")
+                .or(baseline_update.filter(|path| Path::new(path).exists()));
+
+            if let Some(baseline_path) = baseline_path {
+                let format = matches
+                    .get_one::<String>("baseline-format")
+                    .and_then(|name| BaselineFormat::from_name(name))
+                    .unwrap_or(BaselineFormat::Json);
+
+                let renames = matches
+                    .get_many::<(String, String)>("rename-rule")
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect();
+                match baseline::load_baseline(Path::new(baseline_path), format, &renames) {
+                    Ok(fingerprints) => result.apply_baseline(&fingerprints),
+                    Err(e) => {
+                        eprintln!("Error reading baseline {}: {}", baseline_path, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
 
-        self.result.structs.push(struct_info);
-        
-        // Continue visiting
-        syn::visit::visit_item_struct(self, node);
-    }
+            if let Some(max) = matches.get_one::<usize>("max-findings") {
+                result.truncate_findings(*max);
+            }
 
-    fn visit_item_trait(&mut self, node: &'ast ItemTrait) {
-        let (line_start, line_end) = self.get_line_numbers(node.span());
-        
-        let methods = node.items.iter()
-            .filter_map(|item| {
-                if let syn::TraitItem::Fn(method) = item {
-                    Some(method.sig.ident.to_string())
-                } else {
-                    None
+            let grouped = matches.contains_id("group-by");
+            let output = match matches.get_one::<String>("format").map(String::as_str) {
+                _ if matches.get_flag("severity-summary-only") => {
+                    let mut json = serde_json::to_string(&result.severity_counts()).unwrap();
+                    json.push('\n');
+                    json.into_bytes()
                 }
-            })
-            .collect();
+                Some(
+                    "protobuf" | "table" | "github" | "sarif" | "markdown" | "csv" | "dot"
+                    | "junit" | "pretty",
+                ) if grouped =>
+                {
+                    eprintln!("Error: --group-by only applies to JSON output");
+                    std::process::exit(1);
+                }
+                Some("protobuf") => proto::ScanReport::from(&result).encode_to_vec(),
+                Some("github") => github::render(&result, file_path).into_bytes(),
+                Some("sarif") => to_json(&sarif::report(&result, file_path), indent).into_bytes(),
+                Some("markdown") => markdown::render(&[(file_path, &result)]).into_bytes(),
+                Some("csv") => csv::render(&[(file_path, &result)]).into_bytes(),
+                Some("dot") => dot::render(&result).into_bytes(),
+                Some("junit") => junit::render(&[(file_path, &result)]).into_bytes(),
+                Some("pretty") => {
+                    // Escape codes would end up in the file with --output
+                    let colored = !matches.contains_id("output") && pretty::stdout_color();
+                    pretty::render(&result, file_path, colored).into_bytes()
+                }
+                Some("table") => {
+                    let width = matches
+                        .get_one::<usize>("width")
+                        .copied()
+                        .unwrap_or_else(table::terminal_width);
+                    table::render(&result, width).into_bytes()
+                }
+                _ if grouped => to_json(&result.group_by_severity(), indent).into_bytes(),
+                _ => to_json(&result, indent).into_bytes(),
+            };
 
-        let trait_info = ParsedTrait {
-            name: node.ident.to_string(),
-            visibility: Self::visibility_to_string(&node.vis),
-            methods,
-            attributes: Self::extract_attributes(&node.attrs),
-            line_start,
-            line_end,
-        };
+            let written = match matches.get_one::<String>("output") {
+                Some(output_file) => fs::write(output_file, &output),
+                None => io::stdout().write_all(&output),
+            };
+            if let Err(e) = written {
+                eprintln!("Error writing output: {}", e);
+                std::process::exit(1);
+            }
 
-        self.result.traits.push(trait_info);
-        
-        // Continue visiting
-        syn::visit::visit_item_trait(self, node);
+            let failed = matches
+                .get_one::<String>("fail-on")
+                .and_then(|name| Severity::from_name(name))
+                .is_some_and(|min| result.findings().any(|finding| finding.severity >= min));
+            if let (Some(path), Some(snapshot), false) = (baseline_update, snapshot, failed) {
+                if let Err(e) = fs::write(path, snapshot) {
+                    eprintln!("Error writing baseline {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+            if failed {
+                std::process::exit(2);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error parsing file: {}", e);
+            std::process::exit(1);
+        }
     }
+}
 
-    fn visit_item_impl(&mut self, node: &'ast ItemImpl) {
-        let (line_start, line_end) = self.get_line_numbers(node.span());
-        
-        let target_type = quote::quote!(#node.self_ty).to_string();
-        let trait_name = node.trait_.as_ref()
-            .map(|(_, path, _)| quote::quote!(#path).to_string());
-        
-        let methods = node.items.iter()
-            .filter_map(|item| {
-                if let syn::ImplItem::Fn(method) = item {
-                    Some(method.sig.ident.to_string())
-                } else {
-                    None
-                }
-            })
+fn run_dir(scanner: &Scanner, dir: &Path, matches: &clap::ArgMatches) {
+    let format = matches.get_one::<String>("format").map(String::as_str);
+    if !matches!(format, Some("json" | "markdown" | "csv" | "junit")) {
+        eprintln!("Error: --dir only supports json, markdown, csv and junit output");
+        std::process::exit(1);
+    }
+    let mut results = match scanner.scan_directory(dir) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Error reading directory {}: {}", dir.display(), e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(baseline_path) = matches.get_one::<String>("baseline") {
+        let renames = matches
+            .get_many::<(String, String)>("rename-rule")
+            .into_iter()
+            .flatten()
+            .cloned()
             .collect();
+        match baseline::load_dir_baseline(Path::new(baseline_path), &renames) {
+            Ok(baselines) => {
+                for (path, report) in &mut results {
+                    if let Some(fingerprints) = baselines.get(&path.display().to_string()) {
+                        report.apply_baseline(fingerprints);
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading baseline {}: {}", baseline_path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(max) = matches.get_one::<usize>("max-findings") {
+        for (_, report) in &mut results {
+            report.truncate_findings(*max);
+        }
+    }
 
-        let impl_info = ParsedImpl {
-            target_type,
-            trait_name,
-            methods,
-            line_start,
-            line_end,
-        };
-
-        self.result.impl_blocks.push(impl_info);
-        
-        // Continue visiting
-        syn::visit::visit_item_impl(self, node);
+    let paths: Vec<String> = results.iter().map(|(path, _)| path.display().to_string()).collect();
+    let reports: Vec<(&str, &ParseResult)> = paths
+        .iter()
+        .zip(&results)
+        .map(|(path, (_, report))| (path.as_str(), report))
+        .collect();
+    let output = match format {
+        Some("markdown") => markdown::render(&reports),
+        Some("csv") => csv::render(&reports),
+        Some("junit") => junit::render(&reports),
+        _ => {
+            let files: Vec<serde_json::Value> = results
+                .iter()
+                .map(|(path, report)| serde_json::json!({ "path": path, "report": report }))
+                .collect();
+            to_json(&files, matches.get_one::<String>("indent").unwrap())
+        }
+    };
+    let written = match matches.get_one::<String>("output") {
+        Some(output_file) => fs::write(output_file, &output),
+        None => io::stdout().write_all(output.as_bytes()),
+    };
+    if let Err(e) = written {
+        eprintln!("Error writing output: {}", e);
+        std::process::exit(1);
     }
 
-    fn visit_item_use(&mut self, node: &'ast syn::ItemUse) {
-        let use_statement = quote::quote!(#node).to_string();
-        self.result.uses.push(use_statement);
-        
-        // Continue visiting
-        syn::visit::visit_item_use(self, node);
+    if matches.get_flag("fail-on-parse-error") {
+        let broken: Vec<_> =
+            results.iter().filter(|(_, report)| !report.errors.is_empty()).collect();
+        for (path, report) in &broken {
+            eprintln!("{}: {}", path.display(), report.errors.join("; "));
+        }
+        if !broken.is_empty() {
+            std::process::exit(1);
+        }
     }
+    let failed = matches
+        .get_one::<String>("fail-on")
+        .and_then(|name| Severity::from_name(name))
+        .is_some_and(|min| {
+            results
+                .iter()
+                .any(|(_, report)| report.findings().any(|finding| finding.severity >= min))
+        });
+    if failed {
+        std::process::exit(2);
+    }
+}
 
-    fn visit_block(&mut self, node: &'ast syn::Block) {
-        // Note: Unsafe blocks are handled differently in syn
-        // They appear as ExprUnsafe expressions, not as Block unsafety
+// Pretty-printed JSON with a trailing newline, each level indented by `indent`
+fn to_json(value: &impl Serialize, indent: &str) -> String {
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut out = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer).unwrap();
+    out.push(b'\n');
+    String::from_utf8(out).unwrap()
+}
 
-        // Continue visiting
-        syn::visit::visit_block(self, node);
+fn parse_indent(value: &str) -> Result<String, String> {
+    if value == "tab" {
+        return Ok("\t".to_string());
+    }
+    match value.parse::<usize>() {
+        Ok(width) => Ok(" ".repeat(width)),
+        Err(_) => Err(format!("expected a number of spaces or `tab`, got `{}`", value)),
     }
 }
 
-fn parse_rust_file(file_path: &str) -> Result<ParseResult, Box<dyn std::error::Error>> {
-    let source = fs::read_to_string(file_path)?;
-    
-    match syn::parse_file(&source) {
-        Ok(ast) => {
-            let mut visitor = RustVisitor::new(&source);
-            visitor.detect_contract_type(&source);
-            visitor.visit_file(&ast);
-            Ok(visitor.result)
+fn parse_rename(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => {
+            Ok((old.to_string(), new.to_string()))
         }
+        _ => Err(format!("expected OLD=NEW, got `{}`", value)),
+    }
+}
+
+fn validate_config(path: &str) {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
         Err(e) => {
-            let result = ParseResult {
-                functions: Vec::new(),
-                structs: Vec::new(),
-                traits: Vec::new(),
-                impl_blocks: Vec::new(),
-                unsafe_blocks: Vec::new(),
-                attributes: Vec::new(),
-                uses: Vec::new(),
-                contract_type: "unknown".to_string(),
-                errors: vec![format!("Parse error: {}", e)],
-            };
-            Ok(result)
+            eprintln!("Error reading config {}: {}", path, e);
+            std::process::exit(1);
         }
+    };
+
+    let errors = config::validate(&source);
+    if errors.is_empty() {
+        println!("{}: ok", path);
+        return;
     }
+    for error in &errors {
+        eprintln!("{}:{}: {}", path, error.line, error.message);
+    }
+    std::process::exit(1);
 }
 
-fn main() {
-    let matches = Command::new("Rust Parser Helper")
-        .version("0.1.0")
-        .about("Parses Rust smart contracts using syn crate")
-        .arg(
-            Arg::new("file")
-                .help("Rust file to parse")
-                .required(true)
-                .index(1),
-        )
-        .arg(
-            Arg::new("output")
-                .short('o')
-                .long("output")
-                .help("Output file for JSON result")
-                .value_name("FILE"),
-        )
-        .get_matches();
-
-    let file_path = matches.get_one::<String>("file").unwrap();
-    
-    match parse_rust_file(file_path) {
-        Ok(result) => {
-            let json_output = serde_json::to_string_pretty(&result).unwrap();
-            
-            if let Some(output_file) = matches.get_one::<String>("output") {
-                if let Err(e) = fs::write(output_file, &json_output) {
-                    eprintln!("Error writing to output file: {}", e);
-                    std::process::exit(1);
-                }
-            } else {
-                println!("{}", json_output);
+fn run_bench(matches: &clap::ArgMatches) {
+    let corpus: Vec<(String, String)> = match matches.get_one::<String>("corpus") {
+        Some(dir) => match load_corpus(Path::new(dir)) {
+            Ok(corpus) => corpus,
+            Err(e) => {
+                eprintln!("Error reading corpus {}: {}", dir, e);
+                std::process::exit(1);
+            }
+        },
+        None => bench::CORPUS
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect(),
+    };
+    let iterations = *matches.get_one::<usize>("iterations").unwrap();
+
+    let report = bench::run(&Scanner::builder().build(), &corpus, iterations);
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+fn run_doctor() {
+    let mut json = serde_json::to_string_pretty(&doctor::diagnose()).unwrap();
+    json.push('\n');
+    if let Err(e) = io::stdout().write_all(json.as_bytes()) {
+        eprintln!("Error writing output: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run_merge(matches: &clap::ArgMatches) {
+    let mut reports = Vec::new();
+    for path in matches.get_many::<String>("reports").into_iter().flatten() {
+        let read = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| merge::read_reports(path, &contents).map_err(|e| e.to_string()));
+        match read {
+            Ok(read) => reports.extend(read),
+            Err(e) => {
+                eprintln!("Error reading report {}: {}", path, e);
+                std::process::exit(1);
             }
         }
-        Err(e) => {
-            eprintln!("Error parsing file: {}", e);
-            std::process::exit(1);
+    }
+
+    let mut json = serde_json::to_string_pretty(&merge::merge(reports)).unwrap();
+    json.push('\n');
+    let written = match matches.get_one::<String>("output") {
+        Some(output_file) => fs::write(output_file, &json),
+        None => io::stdout().write_all(json.as_bytes()),
+    };
+    if let Err(e) = written {
+        eprintln!("Error writing output: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn load_corpus(dir: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut corpus = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "rs") {
+            let source = fs::read_to_string(&path)?;
+            corpus.push((path.display().to_string(), source));
         }
     }
+    corpus.sort();
+    Ok(corpus)
 }
@@ -0,0 +1,112 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Framework crates and the rule profile each one selects, checked in order
+const PROFILE_DEPENDENCIES: &[(&str, &str)] = &[
+    ("ink", "ink"),
+    ("ink_lang", "ink"),
+    ("ink_storage", "ink"),
+    ("ink_env", "ink"),
+    ("cosmwasm_std", "cosmwasm"),
+    ("anchor_lang", "anchor"),
+    ("near_sdk", "near"),
+];
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    profile: BTreeMap<String, BuildProfile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildProfile {
+    panic: Option<String>,
+}
+
+impl Manifest {
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    // Crate names as they appear on crates.io, honouring `package = "..."` renames
+    fn dependency_names(&self) -> Vec<String> {
+        self.dependencies
+            .iter()
+            .map(|(name, value)| {
+                value
+                    .get("package")
+                    .and_then(|package| package.as_str())
+                    .unwrap_or(name)
+                    .replace('-', "_")
+            })
+            .collect()
+    }
+}
+
+// Walks up from the analyzed file looking for the nearest `Cargo.toml`.
+pub fn find_manifest(source_path: &Path) -> Option<PathBuf> {
+    let start = fs::canonicalize(source_path).ok()?;
+    start
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find(|candidate| candidate.is_file())
+}
+
+// Picks the rule profile from the manifest's dependencies instead of source heuristics.
+pub fn profile_from_manifest(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let dependencies = Manifest::load(path)?.dependency_names();
+
+    let profile = PROFILE_DEPENDENCIES
+        .iter()
+        .find(|(dependency, _)| dependencies.iter().any(|name| name == dependency))
+        .map(|(_, profile)| profile.to_string())
+        .unwrap_or_else(|| "generic".to_string());
+
+    Ok(profile)
+}
+
+// `panic` from `[profile.release]`, the profile contracts are deployed with
+pub fn panic_strategy(path: &Path) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let manifest = Manifest::load(path)?;
+    Ok(manifest.profile.get("release").and_then(|profile| profile.panic.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(contents: &str) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), contents).unwrap();
+        file
+    }
+
+    #[test]
+    fn renamed_dependency_selects_its_package_profile() {
+        let manifest = write_manifest("[dependencies]\nsdk = { package = \"near-sdk\" }\n");
+        assert_eq!(profile_from_manifest(manifest.path()).unwrap(), "near");
+    }
+
+    #[test]
+    fn no_framework_dependency_is_generic() {
+        let manifest = write_manifest("[dependencies]\nserde = \"1\"\n");
+        assert_eq!(profile_from_manifest(manifest.path()).unwrap(), "generic");
+    }
+
+    #[test]
+    fn panic_strategy_comes_from_the_release_profile() {
+        let manifest = write_manifest(
+            "[profile.dev]\npanic = \"unwind\"\n\n[profile.release]\npanic = \"abort\"\n",
+        );
+        assert_eq!(panic_strategy(manifest.path()).unwrap().as_deref(), Some("abort"));
+
+        let unset = write_manifest("[package]\nname = \"c\"\n");
+        assert_eq!(panic_strategy(unset.path()).unwrap(), None);
+    }
+}
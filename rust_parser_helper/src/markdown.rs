@@ -0,0 +1,93 @@
+// Markdown audit report: a severity summary across every scanned file, then a
+// section per file with its findings, most severe first.
+
+use crate::findings::{Finding, Severity};
+use crate::ParseResult;
+
+const SEVERITIES: [Severity; 5] = [
+    Severity::Critical,
+    Severity::High,
+    Severity::Medium,
+    Severity::Low,
+    Severity::Info,
+];
+
+// `reports` pairs each file, as the scan was given it, with its result
+pub fn render(reports: &[(&str, &ParseResult)]) -> String {
+    let mut out = String::from("# Contract scan report\n\n## Summary\n\n");
+    out.push_str("| Severity | Findings |\n| --- | ---: |\n");
+    let mut total = 0;
+    for severity in SEVERITIES {
+        let count = reports
+            .iter()
+            .flat_map(|(_, result)| result.findings())
+            .filter(|finding| finding.severity == severity)
+            .count();
+        total += count;
+        out.push_str(&format!("| {:?} | {} |\n", severity, count));
+    }
+    out.push_str(&format!("| **Total** | **{}** |\n", total));
+
+    for (file, result) in reports {
+        out.push_str(&format!("\n## `{}`\n\n", file));
+        let mut findings: Vec<&Finding> = result.findings().collect();
+        if findings.is_empty() {
+            out.push_str("No findings.\n");
+            continue;
+        }
+        // Stable, so findings of equal severity keep their line order
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+        out.push_str("| Severity | Rule | Lines | Effort | Description |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for finding in findings {
+            let lines = if finding.line_end > finding.line_start {
+                format!("{}-{}", finding.line_start, finding.line_end)
+            } else {
+                finding.line_start.to_string()
+            };
+            let effort = finding
+                .effort
+                .map_or("-".to_string(), |effort| format!("{:?}", effort).to_lowercase());
+            out.push_str(&format!(
+                "| {:?} | `{}` | {} | {} | {} |\n",
+                finding.severity,
+                finding.rule_id,
+                lines,
+                effort,
+                escape_cell(&finding.message)
+            ));
+        }
+    }
+    out
+}
+
+// A table row is a single line, and `|` would end the cell early
+fn escape_cell(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("");
+    line.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_summarizes_and_lists_each_file() {
+        let source = include_str!("../../test_vulnerable_contract.rs");
+        let sample = crate::parse_rust_source(source, "lib.rs");
+        let clean = crate::parse_rust_source("fn f() {}\n", "clean.rs");
+        let report = render(&[("lib.rs", &sample), ("clean.rs", &clean)]);
+
+        let total = sample.findings().count();
+        assert!(report.contains(&format!("| **Total** | **{}** |", total)), "{}", report);
+        assert!(report.contains("\n## `clean.rs`\n\nNo findings.\n"), "{}", report);
+        let rows: Vec<&str> = report.lines().filter(|line| line.starts_with("| High |")).collect();
+        assert!(rows.iter().any(|row| row.starts_with("| High | `unsafe-block` | 44-48 |")));
+    }
+
+    #[test]
+    fn pipes_in_messages_are_escaped() {
+        assert_eq!(escape_cell("a || b\nsecond line"), "a \\|\\| b");
+    }
+}
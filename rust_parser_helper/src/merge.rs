@@ -0,0 +1,170 @@
+// Combines the JSON reports of a sharded scan into one. Reports of the same
+// source (equal `source_hash`) are scanned twice when shards overlap, so their
+// findings are deduplicated by fingerprint, but only against other reports: a
+// single report never repeats a finding, even when two look the same.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::findings::{Finding, Severity};
+use crate::ParseResult;
+
+#[derive(Debug, Serialize)]
+pub struct MergedFinding {
+    // The report file the finding was read from
+    pub report: String,
+    pub category: String,
+    #[serde(flatten)]
+    pub finding: Finding,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MergeSummary {
+    pub total: usize,
+    pub duplicates_removed: usize,
+    pub by_severity: BTreeMap<Severity, usize>,
+    pub by_rule: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MergedReport {
+    pub reports: usize,
+    // Each report's contract type, by report name
+    pub contract_types: BTreeMap<String, String>,
+    pub summary: MergeSummary,
+    pub findings: Vec<MergedFinding>,
+    pub errors: Vec<String>,
+}
+
+// A `--dir` entry, or a whole single-file report
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReportFile {
+    Dir(Vec<DirEntry>),
+    File(Box<ParseResult>),
+}
+
+#[derive(Deserialize)]
+struct DirEntry {
+    path: String,
+    report: ParseResult,
+}
+
+// The reports in a JSON file written by a scan, named by the file they were read
+// from, or for `--dir` output by the path of each scanned file
+pub fn read_reports(name: &str, json: &str) -> serde_json::Result<Vec<(String, ParseResult)>> {
+    Ok(match serde_json::from_str(json)? {
+        ReportFile::Dir(entries) => {
+            entries.into_iter().map(|entry| (entry.path, entry.report)).collect()
+        }
+        ReportFile::File(report) => vec![(name.to_string(), *report)],
+    })
+}
+
+pub fn merge(reports: Vec<(String, ParseResult)>) -> MergedReport {
+    let mut merged = MergedReport {
+        reports: reports.len(),
+        ..MergedReport::default()
+    };
+    // Copies of each (source, fingerprint) kept from the reports merged so far
+    let mut kept: HashMap<(String, String), usize> = HashMap::new();
+
+    for (name, report) in reports {
+        merged.contract_types.insert(name.clone(), report.contract_type.clone());
+        let mut in_report: HashMap<(String, String), usize> = HashMap::new();
+        for (category, list) in report.warning_lists() {
+            for finding in list {
+                let key = (report.source_hash.clone(), finding.fingerprint.clone());
+                let count = in_report.entry(key.clone()).or_default();
+                *count += 1;
+                if *count <= kept.get(&key).copied().unwrap_or(0) {
+                    merged.summary.duplicates_removed += 1;
+                    continue;
+                }
+                *merged.summary.by_severity.entry(finding.severity).or_default() += 1;
+                *merged.summary.by_rule.entry(finding.rule_id.clone()).or_default() += 1;
+                merged.findings.push(MergedFinding {
+                    report: name.clone(),
+                    category: category.to_string(),
+                    finding: finding.clone(),
+                });
+            }
+        }
+        for (key, count) in in_report {
+            let total = kept.entry(key).or_default();
+            *total = (*total).max(count);
+        }
+        merged
+            .errors
+            .extend(report.errors.iter().map(|error| format!("{}: {}", name, error)));
+    }
+
+    merged.summary.total = merged.findings.len();
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_rust_source;
+
+    fn report(source: &str) -> ParseResult {
+        parse_rust_source(source, "test.rs")
+    }
+
+    #[test]
+    fn merged_counts_cover_every_report() {
+        let unwrap = report("fn a(v: &[u8]) -> u8 { *v.first().unwrap() }");
+        let cast = report("fn b(n: i64) -> u64 { n as u64 }");
+        let expected = unwrap.findings().count() + cast.findings().count();
+
+        let merged = merge(vec![("a.json".into(), unwrap), ("b.json".into(), cast)]);
+        assert_eq!(merged.reports, 2);
+        assert_eq!(merged.summary.total, expected);
+        assert_eq!(merged.summary.duplicates_removed, 0);
+        assert_eq!(merged.summary.by_rule["empty-collection-unwrap"], 1);
+        assert_eq!(merged.summary.by_rule["sign-cast"], 1);
+        let by_severity: usize = merged.summary.by_severity.values().sum();
+        assert_eq!(by_severity, expected);
+    }
+
+    #[test]
+    fn overlapping_shards_are_deduplicated() {
+        let source = "fn a(v: &[u8]) -> u8 { *v.first().unwrap() }";
+        let shards = vec![("a.json".into(), report(source)), ("b.json".into(), report(source))];
+        let merged = merge(shards);
+        assert_eq!(merged.summary.total, 1);
+        assert_eq!(merged.summary.duplicates_removed, 1);
+    }
+
+    #[test]
+    fn equal_findings_within_one_report_are_all_kept() {
+        let mut twice = report("fn a(v: &[u8]) -> u8 { *v.first().unwrap() }");
+        let copy = twice.empty_collection_warnings[0].clone();
+        twice.empty_collection_warnings.push(copy);
+
+        let merged = merge(vec![("a.json".into(), twice)]);
+        assert_eq!(merged.summary.by_rule["empty-collection-unwrap"], 2);
+        assert_eq!(merged.summary.duplicates_removed, 0);
+    }
+
+    #[test]
+    fn directory_output_is_read_as_one_report_per_file() {
+        let unwrap = report("fn a(v: &[u8]) -> u8 { *v.first().unwrap() }");
+        let cast = report("fn b(n: i64) -> u64 { n as u64 }");
+        let dir = serde_json::json!([
+            { "path": "src/a.rs", "report": unwrap },
+            { "path": "src/b.rs", "report": cast },
+        ]);
+        let mut reports = read_reports("dir.json", &dir.to_string()).unwrap();
+        let single = serde_json::to_string(&report("fn c() {}")).unwrap();
+        reports.extend(read_reports("c.json", &single).unwrap());
+
+        let names: Vec<&str> = reports.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["src/a.rs", "src/b.rs", "c.json"]);
+        let merged = merge(reports);
+        assert_eq!(merged.reports, 3);
+        assert_eq!(merged.findings[0].report, "src/a.rs");
+        assert_eq!(merged.summary.by_rule["sign-cast"], 1);
+    }
+}
@@ -0,0 +1,154 @@
+// Custom rules loaded from a `cdylib` at runtime. Rust has no stable ABI, so a
+// plugin must be built against the same version of this crate with the same
+// compiler; `ABI_VERSION` names both, and a plugin built with anything else is
+// refused before we call into it.
+
+use libloading::Library;
+use std::ffi::{c_char, CStr};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::findings::Finding;
+
+pub const ABI_VERSION: &str =
+    concat!(env!("CARGO_PKG_VERSION"), ", ", env!("QUARD_RUSTC_VERSION"));
+
+// `ABI_VERSION` as the NUL-terminated bytes `declare_plugin!` exports, so the
+// loader can read it without relying on how the plugin's compiler lays out `&str`
+#[doc(hidden)]
+pub const ABI_VERSION_NUL: [u8; ABI_VERSION.len() + 1] = nul_terminated(ABI_VERSION);
+
+const fn nul_terminated<const N: usize>(text: &str) -> [u8; N] {
+    let bytes = text.as_bytes();
+    let mut out = [0; N];
+    let mut index = 0;
+    while index < bytes.len() {
+        out[index] = bytes[index];
+        index += 1;
+    }
+    out
+}
+
+const ABI_VERSION_SYMBOL: &[u8] = b"QUARD_PLUGIN_ABI_VERSION";
+const RULES_SYMBOL: &[u8] = b"quard_plugin_rules";
+
+pub trait PluginRule: Send + Sync {
+    fn id(&self) -> &str;
+    fn check(&self, source: &str) -> Vec<Finding>;
+}
+
+// Exports the symbols the loader looks for; call once at the root of a plugin crate
+#[macro_export]
+macro_rules! declare_plugin {
+    ($($rule:expr),* $(,)?) => {
+        #[no_mangle]
+        pub static QUARD_PLUGIN_ABI_VERSION: [u8; $crate::plugin::ABI_VERSION.len() + 1] =
+            $crate::plugin::ABI_VERSION_NUL;
+
+        #[no_mangle]
+        pub fn quard_plugin_rules() -> Vec<Box<dyn $crate::plugin::PluginRule>> {
+            vec![$(Box::new($rule)),*]
+        }
+    };
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Load { path: PathBuf, source: libloading::Error },
+    AbiMismatch { path: PathBuf, expected: &'static str, found: String },
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Load { path, source } => {
+                write!(f, "failed to load plugin {}: {}", path.display(), source)
+            }
+            PluginError::AbiMismatch { path, expected, found } => write!(
+                f,
+                "plugin {} was built for ABI version `{}`, expected `{}`",
+                path.display(),
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PluginError::Load { source, .. } => Some(source),
+            PluginError::AbiMismatch { .. } => None,
+        }
+    }
+}
+
+pub struct Plugin {
+    rules: Vec<Box<dyn PluginRule>>,
+    // Declared after `rules` so the code they point into is unloaded last
+    _library: Library,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        let load_error = |source| PluginError::Load {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        // SAFETY: loading runs the library's initializers; plugins are trusted code
+        // the user asked for explicitly with `--plugin`.
+        let library = unsafe { Library::new(path) }.map_err(load_error)?;
+
+        // SAFETY: the symbol is the NUL-terminated byte string emitted by `declare_plugin!`
+        let found = unsafe { library.get::<*const c_char>(ABI_VERSION_SYMBOL) }
+            .map(|symbol| unsafe { CStr::from_ptr(*symbol) }.to_string_lossy().into_owned())
+            .map_err(load_error)?;
+        if found != ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                path: path.to_path_buf(),
+                expected: ABI_VERSION,
+                found,
+            });
+        }
+
+        // SAFETY: the ABI version matched, so the function has the signature
+        // `declare_plugin!` gives it
+        let rules = unsafe {
+            let constructor = library
+                .get::<fn() -> Vec<Box<dyn PluginRule>>>(RULES_SYMBOL)
+                .map_err(load_error)?;
+            constructor()
+        };
+
+        Ok(Self {
+            rules,
+            _library: library,
+        })
+    }
+
+    pub fn rules(&self) -> &[Box<dyn PluginRule>] {
+        &self.rules
+    }
+}
+
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids: Vec<&str> = self.rules.iter().map(|rule| rule.id()).collect();
+        f.debug_struct("Plugin").field("rules", &ids).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_version_names_the_crate_and_the_compiler() {
+        let crate_version = concat!(env!("CARGO_PKG_VERSION"), ", rustc ");
+        assert!(ABI_VERSION.starts_with(crate_version), "{}", ABI_VERSION);
+        let exported = CStr::from_bytes_with_nul(&ABI_VERSION_NUL).unwrap();
+        assert_eq!(exported.to_str(), Ok(ABI_VERSION));
+    }
+}
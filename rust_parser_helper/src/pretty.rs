@@ -0,0 +1,110 @@
+// Findings for reading in a terminal: one line each, led by a glyph and
+// colored by severity when the output is a terminal that wants color.
+
+use std::io::IsTerminal;
+
+use crate::findings::Severity;
+use crate::ParseResult;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+
+// Whether to color output, given `NO_COLOR` and whether it goes to a terminal.
+// Any non-empty `NO_COLOR` turns color off, as https://no-color.org asks.
+pub fn use_color(no_color: Option<&str>, is_terminal: bool) -> bool {
+    is_terminal && no_color.is_none_or(str::is_empty)
+}
+
+// `use_color` for stdout and this process's environment
+pub fn stdout_color() -> bool {
+    let no_color = std::env::var("NO_COLOR").ok();
+    use_color(no_color.as_deref(), std::io::stdout().is_terminal())
+}
+
+pub fn glyph(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "✖",
+        Severity::High => "✗",
+        Severity::Medium => "▲",
+        Severity::Low => "●",
+        Severity::Info => "ℹ",
+    }
+}
+
+// Red for Critical/High, yellow for Medium, blue for Low/Info
+pub fn color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "\x1b[31m",
+        Severity::Medium => "\x1b[33m",
+        Severity::Low | Severity::Info => "\x1b[34m",
+    }
+}
+
+pub fn render(result: &ParseResult, file: &str, colored: bool) -> String {
+    let paint = |code: &str, text: &str| {
+        if colored {
+            format!("{}{}{}", code, text, RESET)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    let mut count = 0;
+    for finding in result.findings() {
+        let label = format!(
+            "{} {:<8}",
+            glyph(finding.severity),
+            format!("{:?}", finding.severity).to_lowercase()
+        );
+        let location = if finding.line_end > finding.line_start {
+            format!("{}:{}-{}", file, finding.line_start, finding.line_end)
+        } else {
+            format!("{}:{}", file, finding.line_start)
+        };
+        out.push_str(&format!(
+            "{} {} {}\n    {}\n",
+            paint(color(finding.severity), &label),
+            paint(BOLD, &finding.rule_id),
+            location,
+            finding.message
+        ));
+        count += 1;
+    }
+    if count == 0 {
+        out.push_str("No findings.\n");
+    } else {
+        out.push_str(&format!(
+            "\n{} finding{} in {}\n",
+            count,
+            if count == 1 { "" } else { "s" },
+            file
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_needs_a_terminal_and_no_no_color() {
+        assert!(use_color(None, true));
+        assert!(use_color(Some(""), true));
+        assert!(!use_color(Some("1"), true));
+        assert!(!use_color(None, false));
+    }
+
+    #[test]
+    fn findings_are_painted_only_when_colored() {
+        let result = crate::parse_rust_source("fn f(o: Option<u8>) -> u8 { o.unwrap() }\n", "a.rs");
+        let plain = render(&result, "a.rs", false);
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.starts_with("▲ medium   panic-unwrap a.rs:1\n"), "{}", plain);
+        assert!(plain.ends_with("\n1 finding in a.rs\n"), "{}", plain);
+
+        let colored = render(&result, "a.rs", true);
+        assert!(colored.starts_with("\x1b[33m▲ medium  \x1b[0m \x1b[1mpanic-unwrap\x1b[0m"));
+    }
+}
@@ -0,0 +1,653 @@
+// Protobuf form of the report, kept in sync with proto/scan_report.proto by
+// hand so building doesn't need `protoc`; a test checks the field names and
+// tags against the `.proto` file.
+
+use std::collections::BTreeMap;
+
+use crate::findings;
+use crate::{
+    CallEdge as ParsedCallEdge, ParseResult, ParsedField, ParsedFunction, ParsedImpl,
+    ParsedParameter, ParsedStruct, ParsedTrait, ParsedUnsafeBlock, TaintEdge as ParsedTaintEdge,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Severity {
+    Info = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Critical = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Effort {
+    Trivial = 0,
+    Moderate = 1,
+    Significant = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Finding {
+    #[prost(string, tag = "1")]
+    pub rule_id: String,
+    #[prost(string, tag = "2")]
+    pub message: String,
+    #[prost(enumeration = "Severity", tag = "3")]
+    pub severity: i32,
+    #[prost(uint64, tag = "4")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "5")]
+    pub line_end: u64,
+    #[prost(string, tag = "6")]
+    pub fingerprint: String,
+    #[prost(string, optional, tag = "7")]
+    pub feature: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub suggested_fix: Option<String>,
+    #[prost(enumeration = "Effort", optional, tag = "9")]
+    pub effort: Option<i32>,
+    #[prost(string, optional, tag = "10")]
+    pub item: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FindingGroup {
+    #[prost(string, tag = "1")]
+    pub category: String,
+    #[prost(message, repeated, tag = "2")]
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Parameter {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub param_type: String,
+    #[prost(bool, tag = "3")]
+    pub is_mutable: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Function {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub visibility: String,
+    #[prost(message, repeated, tag = "3")]
+    pub parameters: Vec<Parameter>,
+    #[prost(string, optional, tag = "4")]
+    pub return_type: Option<String>,
+    #[prost(string, repeated, tag = "5")]
+    pub attributes: Vec<String>,
+    #[prost(bool, tag = "6")]
+    pub is_async: bool,
+    #[prost(bool, tag = "7")]
+    pub is_unsafe: bool,
+    #[prost(uint64, tag = "8")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "9")]
+    pub line_end: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Field {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub field_type: String,
+    #[prost(string, tag = "3")]
+    pub visibility: String,
+    #[prost(uint64, optional, tag = "4")]
+    pub estimated_size: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Struct {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub visibility: String,
+    #[prost(message, repeated, tag = "3")]
+    pub fields: Vec<Field>,
+    #[prost(string, repeated, tag = "4")]
+    pub attributes: Vec<String>,
+    #[prost(uint64, tag = "5")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "6")]
+    pub line_end: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Trait {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub visibility: String,
+    #[prost(string, repeated, tag = "3")]
+    pub methods: Vec<String>,
+    #[prost(string, repeated, tag = "4")]
+    pub attributes: Vec<String>,
+    #[prost(uint64, tag = "5")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "6")]
+    pub line_end: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Impl {
+    #[prost(string, tag = "1")]
+    pub target_type: String,
+    #[prost(string, optional, tag = "2")]
+    pub trait_name: Option<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub methods: Vec<String>,
+    #[prost(uint64, tag = "4")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "5")]
+    pub line_end: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UnsafeBlock {
+    #[prost(uint64, tag = "1")]
+    pub line_start: u64,
+    #[prost(uint64, tag = "2")]
+    pub line_end: u64,
+    #[prost(string, tag = "3")]
+    pub context: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TaintEdge {
+    #[prost(string, tag = "1")]
+    pub function: String,
+    #[prost(string, tag = "2")]
+    pub param: String,
+    #[prost(string, tag = "3")]
+    pub sink: String,
+    #[prost(uint64, tag = "4")]
+    pub line: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CallEdge {
+    #[prost(string, tag = "1")]
+    pub caller: String,
+    #[prost(string, tag = "2")]
+    pub callee: String,
+    #[prost(bool, tag = "3")]
+    pub external: bool,
+    #[prost(uint64, tag = "4")]
+    pub line: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScanReport {
+    #[prost(message, repeated, tag = "1")]
+    pub functions: Vec<Function>,
+    #[prost(message, repeated, tag = "2")]
+    pub structs: Vec<Struct>,
+    #[prost(message, repeated, tag = "3")]
+    pub traits: Vec<Trait>,
+    #[prost(message, repeated, tag = "4")]
+    pub impl_blocks: Vec<Impl>,
+    #[prost(message, repeated, tag = "5")]
+    pub unsafe_blocks: Vec<UnsafeBlock>,
+    #[prost(string, repeated, tag = "6")]
+    pub attributes: Vec<String>,
+    #[prost(string, repeated, tag = "7")]
+    pub uses: Vec<String>,
+    #[prost(string, tag = "8")]
+    pub contract_type: String,
+    #[prost(string, tag = "9")]
+    pub profile: String,
+    #[prost(float, tag = "10")]
+    pub analysis_coverage: f32,
+    #[prost(string, repeated, tag = "11")]
+    pub active_features: Vec<String>,
+    #[prost(message, repeated, tag = "12")]
+    pub warnings: Vec<FindingGroup>,
+    #[prost(uint64, tag = "13")]
+    pub baseline_suppressed: u64,
+    #[prost(string, repeated, tag = "14")]
+    pub errors: Vec<String>,
+    #[prost(uint64, tag = "15")]
+    pub findings_truncated: u64,
+    #[prost(string, tag = "16")]
+    pub source_hash: String,
+    #[prost(uint64, tag = "17")]
+    pub functions_from_cache: u64,
+    #[prost(btree_map = "string, uint64", tag = "18")]
+    pub import_summary: BTreeMap<String, u64>,
+    #[prost(string, optional, tag = "19")]
+    pub panic_strategy: Option<String>,
+    #[prost(bool, tag = "20")]
+    pub line_info_available: bool,
+    #[prost(message, repeated, tag = "21")]
+    pub taint: Vec<TaintEdge>,
+    #[prost(string, tag = "22")]
+    pub ruleset_hash: String,
+    #[prost(message, repeated, tag = "23")]
+    pub call_edges: Vec<CallEdge>,
+}
+
+impl From<findings::Severity> for Severity {
+    fn from(severity: findings::Severity) -> Self {
+        match severity {
+            findings::Severity::Info => Self::Info,
+            findings::Severity::Low => Self::Low,
+            findings::Severity::Medium => Self::Medium,
+            findings::Severity::High => Self::High,
+            findings::Severity::Critical => Self::Critical,
+        }
+    }
+}
+
+impl From<Severity> for findings::Severity {
+    fn from(severity: Severity) -> Self {
+        match severity {
+            Severity::Info => Self::Info,
+            Severity::Low => Self::Low,
+            Severity::Medium => Self::Medium,
+            Severity::High => Self::High,
+            Severity::Critical => Self::Critical,
+        }
+    }
+}
+
+impl From<findings::Effort> for Effort {
+    fn from(effort: findings::Effort) -> Self {
+        match effort {
+            findings::Effort::Trivial => Self::Trivial,
+            findings::Effort::Moderate => Self::Moderate,
+            findings::Effort::Significant => Self::Significant,
+        }
+    }
+}
+
+impl From<Effort> for findings::Effort {
+    fn from(effort: Effort) -> Self {
+        match effort {
+            Effort::Trivial => Self::Trivial,
+            Effort::Moderate => Self::Moderate,
+            Effort::Significant => Self::Significant,
+        }
+    }
+}
+
+impl From<&findings::Finding> for Finding {
+    fn from(finding: &findings::Finding) -> Self {
+        Self {
+            rule_id: finding.rule_id.clone(),
+            message: finding.message.clone(),
+            severity: Severity::from(finding.severity) as i32,
+            line_start: finding.line_start as u64,
+            line_end: finding.line_end as u64,
+            fingerprint: finding.fingerprint.clone(),
+            feature: finding.feature.clone(),
+            suggested_fix: finding.suggested_fix.clone(),
+            effort: finding.effort.map(|effort| Effort::from(effort) as i32),
+            item: finding.item.clone(),
+        }
+    }
+}
+
+impl From<Finding> for findings::Finding {
+    fn from(finding: Finding) -> Self {
+        Self {
+            severity: finding.severity().into(),
+            rule_id: finding.rule_id,
+            message: finding.message,
+            line_start: finding.line_start as usize,
+            line_end: finding.line_end as usize,
+            fingerprint: finding.fingerprint,
+            feature: finding.feature,
+            suggested_fix: finding.suggested_fix,
+            effort: finding.effort.and_then(|effort| Effort::try_from(effort).ok()).map(Into::into),
+            item: finding.item,
+        }
+    }
+}
+
+impl From<&ParseResult> for ScanReport {
+    fn from(result: &ParseResult) -> Self {
+        Self {
+            functions: result
+                .functions
+                .iter()
+                .map(|function| Function {
+                    name: function.name.clone(),
+                    visibility: function.visibility.clone(),
+                    parameters: function
+                        .parameters
+                        .iter()
+                        .map(|parameter| Parameter {
+                            name: parameter.name.clone(),
+                            param_type: parameter.param_type.clone(),
+                            is_mutable: parameter.is_mutable,
+                        })
+                        .collect(),
+                    return_type: function.return_type.clone(),
+                    attributes: function.attributes.clone(),
+                    is_async: function.is_async,
+                    is_unsafe: function.is_unsafe,
+                    line_start: function.line_start as u64,
+                    line_end: function.line_end as u64,
+                })
+                .collect(),
+            structs: result
+                .structs
+                .iter()
+                .map(|item| Struct {
+                    name: item.name.clone(),
+                    visibility: item.visibility.clone(),
+                    fields: item
+                        .fields
+                        .iter()
+                        .map(|field| Field {
+                            name: field.name.clone(),
+                            field_type: field.field_type.clone(),
+                            visibility: field.visibility.clone(),
+                            estimated_size: field.estimated_size.map(|size| size as u64),
+                        })
+                        .collect(),
+                    attributes: item.attributes.clone(),
+                    line_start: item.line_start as u64,
+                    line_end: item.line_end as u64,
+                })
+                .collect(),
+            traits: result
+                .traits
+                .iter()
+                .map(|item| Trait {
+                    name: item.name.clone(),
+                    visibility: item.visibility.clone(),
+                    methods: item.methods.clone(),
+                    attributes: item.attributes.clone(),
+                    line_start: item.line_start as u64,
+                    line_end: item.line_end as u64,
+                })
+                .collect(),
+            impl_blocks: result
+                .impl_blocks
+                .iter()
+                .map(|item| Impl {
+                    target_type: item.target_type.clone(),
+                    trait_name: item.trait_name.clone(),
+                    methods: item.methods.clone(),
+                    line_start: item.line_start as u64,
+                    line_end: item.line_end as u64,
+                })
+                .collect(),
+            unsafe_blocks: result
+                .unsafe_blocks
+                .iter()
+                .map(|block| UnsafeBlock {
+                    line_start: block.line_start as u64,
+                    line_end: block.line_end as u64,
+                    context: block.context.clone(),
+                })
+                .collect(),
+            attributes: result.attributes.clone(),
+            uses: result.uses.clone(),
+            contract_type: result.contract_type.clone(),
+            profile: result.profile.clone(),
+            analysis_coverage: result.analysis_coverage,
+            active_features: result.active_features.clone(),
+            warnings: result
+                .warning_lists()
+                .into_iter()
+                .map(|(category, findings)| FindingGroup {
+                    category: category.to_string(),
+                    findings: findings.iter().map(Finding::from).collect(),
+                })
+                .collect(),
+            baseline_suppressed: result.baseline_suppressed as u64,
+            errors: result.errors.clone(),
+            findings_truncated: result.findings_truncated as u64,
+            source_hash: result.source_hash.clone(),
+            functions_from_cache: result.functions_from_cache as u64,
+            panic_strategy: result.panic_strategy.clone(),
+            line_info_available: result.line_info_available,
+            ruleset_hash: result.ruleset_hash.clone(),
+            taint: result
+                .taint
+                .iter()
+                .map(|edge| TaintEdge {
+                    function: edge.function.clone(),
+                    param: edge.param.clone(),
+                    sink: edge.sink.clone(),
+                    line: edge.line as u64,
+                })
+                .collect(),
+            call_edges: result
+                .call_edges
+                .iter()
+                .map(|edge| CallEdge {
+                    caller: edge.caller.clone(),
+                    callee: edge.callee.clone(),
+                    external: edge.external,
+                    line: edge.line as u64,
+                })
+                .collect(),
+            import_summary: result
+                .import_summary
+                .iter()
+                .map(|(name, count)| (name.clone(), *count as u64))
+                .collect(),
+        }
+    }
+}
+
+impl From<ScanReport> for ParseResult {
+    fn from(report: ScanReport) -> Self {
+        let mut result = ParseResult {
+            functions: report
+                .functions
+                .into_iter()
+                .map(|function| ParsedFunction {
+                    name: function.name,
+                    visibility: function.visibility,
+                    parameters: function
+                        .parameters
+                        .into_iter()
+                        .map(|parameter| ParsedParameter {
+                            name: parameter.name,
+                            param_type: parameter.param_type,
+                            is_mutable: parameter.is_mutable,
+                        })
+                        .collect(),
+                    return_type: function.return_type,
+                    attributes: function.attributes,
+                    is_async: function.is_async,
+                    is_unsafe: function.is_unsafe,
+                    line_start: function.line_start as usize,
+                    line_end: function.line_end as usize,
+                })
+                .collect(),
+            structs: report
+                .structs
+                .into_iter()
+                .map(|item| ParsedStruct {
+                    name: item.name,
+                    visibility: item.visibility,
+                    fields: item
+                        .fields
+                        .into_iter()
+                        .map(|field| ParsedField {
+                            name: field.name,
+                            field_type: field.field_type,
+                            visibility: field.visibility,
+                            estimated_size: field.estimated_size.map(|size| size as usize),
+                        })
+                        .collect(),
+                    attributes: item.attributes,
+                    line_start: item.line_start as usize,
+                    line_end: item.line_end as usize,
+                })
+                .collect(),
+            traits: report
+                .traits
+                .into_iter()
+                .map(|item| ParsedTrait {
+                    name: item.name,
+                    visibility: item.visibility,
+                    methods: item.methods,
+                    attributes: item.attributes,
+                    line_start: item.line_start as usize,
+                    line_end: item.line_end as usize,
+                })
+                .collect(),
+            impl_blocks: report
+                .impl_blocks
+                .into_iter()
+                .map(|item| ParsedImpl {
+                    target_type: item.target_type,
+                    trait_name: item.trait_name,
+                    methods: item.methods,
+                    line_start: item.line_start as usize,
+                    line_end: item.line_end as usize,
+                })
+                .collect(),
+            unsafe_blocks: report
+                .unsafe_blocks
+                .into_iter()
+                .map(|block| ParsedUnsafeBlock {
+                    line_start: block.line_start as usize,
+                    line_end: block.line_end as usize,
+                    context: block.context,
+                })
+                .collect(),
+            attributes: report.attributes,
+            uses: report.uses,
+            contract_type: report.contract_type,
+            profile: report.profile,
+            analysis_coverage: report.analysis_coverage,
+            active_features: report.active_features,
+            baseline_suppressed: report.baseline_suppressed as usize,
+            errors: report.errors,
+            findings_truncated: report.findings_truncated as usize,
+            source_hash: report.source_hash,
+            functions_from_cache: report.functions_from_cache as usize,
+            panic_strategy: report.panic_strategy,
+            line_info_available: report.line_info_available,
+            ruleset_hash: report.ruleset_hash,
+            taint: report
+                .taint
+                .into_iter()
+                .map(|edge| ParsedTaintEdge {
+                    function: edge.function,
+                    param: edge.param,
+                    sink: edge.sink,
+                    line: edge.line as usize,
+                })
+                .collect(),
+            call_edges: report
+                .call_edges
+                .into_iter()
+                .map(|edge| ParsedCallEdge {
+                    caller: edge.caller,
+                    callee: edge.callee,
+                    external: edge.external,
+                    line: edge.line as usize,
+                })
+                .collect(),
+            import_summary: report
+                .import_summary
+                .into_iter()
+                .map(|(name, count)| (name, count as usize))
+                .collect(),
+            ..ParseResult::default()
+        };
+
+        let mut groups = report.warnings;
+        for (category, list) in result.warning_lists_mut() {
+            if let Some(group) = groups.iter_mut().find(|group| group.category == category) {
+                *list = std::mem::take(&mut group.findings)
+                    .into_iter()
+                    .map(findings::Finding::from)
+                    .collect();
+            }
+        }
+        result.collect_findings();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn sample_report_round_trips_through_protobuf() {
+        let source = include_str!("../../test_vulnerable_contract.rs");
+        let result = crate::parse_rust_source(source, "test.rs");
+
+        let bytes = ScanReport::from(&result).encode_to_vec();
+        let decoded = ParseResult::from(ScanReport::decode(bytes.as_slice()).unwrap());
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&result).unwrap()
+        );
+    }
+
+    // Field names and tags per message, from the `.proto` file
+    fn proto_fields() -> BTreeMap<String, Vec<(String, u32)>> {
+        let mut messages = BTreeMap::new();
+        let mut current: Option<String> = None;
+        for line in include_str!("../proto/scan_report.proto").lines() {
+            let line = line.split("//").next().unwrap().trim();
+            if let Some(name) = line.strip_prefix("message ") {
+                let name = name.trim_end_matches('{').trim().to_string();
+                messages.insert(name.clone(), Vec::new());
+                current = Some(name);
+            } else if line.starts_with("enum ") || line == "}" {
+                current = None;
+            } else if let (Some(message), Some((decl, tag))) = (&current, line.split_once('=')) {
+                let name = decl.split_whitespace().last().unwrap().to_string();
+                let tag = tag.trim().trim_end_matches(';').parse().unwrap();
+                messages.get_mut(message).unwrap().push((name, tag));
+            }
+        }
+        messages
+    }
+
+    // The same, from the `#[prost(..)]` attributes of the structs in this file
+    fn rust_fields() -> BTreeMap<String, Vec<(String, u32)>> {
+        let file = syn::parse_file(include_str!("proto.rs")).unwrap();
+        let mut messages = BTreeMap::new();
+        for item in file.items {
+            let syn::Item::Struct(item) = item else { continue };
+            let mut fields = Vec::new();
+            for field in &item.fields {
+                let attr = field.attrs.iter().find(|attr| attr.path().is_ident("prost")).unwrap();
+                let mut tag = None;
+                attr.parse_nested_meta(|meta| {
+                    if meta.input.peek(syn::Token![=]) {
+                        let value: syn::LitStr = meta.value()?.parse()?;
+                        if meta.path.is_ident("tag") {
+                            tag = Some(value.value().parse().unwrap());
+                        }
+                    }
+                    Ok(())
+                })
+                .unwrap();
+                fields.push((field.ident.as_ref().unwrap().to_string(), tag.unwrap()));
+            }
+            messages.insert(item.ident.to_string(), fields);
+        }
+        messages
+    }
+
+    #[test]
+    fn structs_match_the_proto_file() {
+        let expected = proto_fields();
+        assert!(expected.contains_key("ScanReport"));
+        assert_eq!(rust_fields(), expected);
+    }
+}
@@ -0,0 +1,653 @@
+use crate::findings::{Confidence, Effort, Severity};
+
+#[derive(Debug)]
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub confidence: Confidence,
+    // Rough size of the fix, for planning remediation work
+    pub effort: Effort,
+    // Profiles the rule runs under; empty means every profile
+    pub profiles: &'static [&'static str],
+    pub rationale: &'static str,
+    pub remediation: &'static str,
+}
+
+impl Rule {
+    pub fn applies_to(&self, profile: &str) -> bool {
+        self.profiles.is_empty() || self.profiles.contains(&profile)
+    }
+}
+
+pub const EMPTY_COLLECTION_UNWRAP: Rule = Rule {
+    id: "empty-collection-unwrap",
+    severity: Severity::Medium,
+    confidence: Confidence::High,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`.first()` and `.last()` return `None` on an empty collection, so unwrapping \
+                them aborts the call whenever the collection happens to be empty.",
+    remediation: "Bind the element with `if let Some(x) = v.first()` and handle the empty case.",
+};
+
+pub const STORAGE_LAYOUT: Rule = Rule {
+    id: "storage-layout",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Significant,
+    profiles: &["ink"],
+    rationale: "Contract storage fields must implement ink!'s storage layout traits; shared \
+                pointers, interior mutability, std hash collections and raw pointers do not \
+                and fail to compile or encode.",
+    remediation: "Use `ink::storage::Mapping` or `ink_prelude` collections and owned values.",
+};
+
+pub const TIMESTAMP_EQUALITY: Rule = Rule {
+    id: "timestamp-equality",
+    severity: Severity::Low,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Blocks don't land at exact times, so a deadline compared with `==`/`!=` against \
+                `block_timestamp`/`block_number` can be skipped over and never match.",
+    remediation: "Compare deadlines with `>=`/`<=` so any block past the deadline is caught.",
+};
+
+pub const INVARIANT_ASSERT: Rule = Rule {
+    id: "invariant-assert",
+    severity: Severity::Info,
+    confidence: Confidence::Low,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "An `assert!` over internal state alone checks an invariant rather than caller \
+                input; it still ships in the contract binary and adds to its size.",
+    remediation: "Use `debug_assert!` for invariants, or return an error if the check guards input.",
+};
+
+pub const EVENT_ORDERING: Rule = Rule {
+    id: "event-ordering",
+    severity: Severity::Info,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Events emitted after an external call can describe state the callee may \
+                already have changed through re-entrancy, misleading off-chain indexers.",
+    remediation: "Update state and emit events before making the external call.",
+};
+
+pub const DUPLICATE_LITERAL: Rule = Rule {
+    id: "duplicate-literal",
+    severity: Severity::Info,
+    confidence: Confidence::High,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "The same large byte-array or string literal repeated across functions bloats \
+                the contract and invites the copies drifting out of sync.",
+    remediation: "Declare the value once as a `const` and reference it everywhere.",
+};
+
+pub const NO_ACCESS_CONTROL: Rule = Rule {
+    id: "no-access-control",
+    severity: Severity::Medium,
+    confidence: Confidence::Low,
+    effort: Effort::Significant,
+    profiles: &["ink"],
+    rationale: "A storage struct with no owner, admin or allowlist field has nothing to check \
+                callers against, so every state-mutating message is open to anyone.",
+    remediation: "Store the privileged account(s) and check `self.env().caller()` against them.",
+};
+
+pub const UNSAFE_WITH_INPUT: Rule = Rule {
+    id: "unsafe-with-input",
+    severity: Severity::Critical,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "An unsafe block that operates on parameters or caller-derived values lets an \
+                attacker choose what the unchecked code reads or writes.",
+    remediation: "Validate the input before the unsafe block, or replace it with safe code.",
+};
+
+pub const MISSING_STORAGE_STRUCT: Rule = Rule {
+    id: "missing-storage-struct",
+    severity: Severity::High,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "Every ink! contract needs exactly one struct marked `#[ink(storage)]`; without \
+                it the contract is malformed and won't compile.",
+    remediation: "Mark the contract's state struct with `#[ink(storage)]`.",
+};
+
+pub const SIGN_CAST: Rule = Rule {
+    id: "sign-cast",
+    severity: Severity::High,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`as` from a signed to an unsigned integer reinterprets the bits, so a negative \
+                `i128` silently becomes an enormous `u128` balance.",
+    remediation: "Convert with `u128::try_from(x)` and handle the error for negative values.",
+};
+
+pub const STALE_CALLER: Rule = Rule {
+    id: "stale-caller",
+    severity: Severity::High,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "`caller()` identifies whoever sent the current call; a copy kept in storage \
+                and compared in a later call grants that account standing rights nobody set up \
+                deliberately, and whichever call wrote it last wins.",
+    remediation: "Store privileged accounts in an owner/role field set by the constructor or \
+                  an access-controlled setter, and authorize against that.",
+};
+
+pub const ERROR_SWALLOW: Rule = Rule {
+    id: "error-swallow",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Calling `.ok()` or `.map_err(..)` and dropping the value throws the error away, \
+                so a failed transfer or call looks exactly like a successful one.",
+    remediation: "Propagate the error with `?`, or match on it and handle the failure.",
+};
+
+pub const TIMING_ATTACK: Rule = Rule {
+    id: "timing-attack",
+    severity: Severity::Medium,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "`==` on byte buffers returns at the first mismatching byte, so the time a \
+                comparison takes leaks how much of a guessed hash, signature or key is right.",
+    remediation: "Compare secrets in constant time, for example with `subtle::ConstantTimeEq`.",
+};
+
+pub const ENV_UNWRAP: Rule = Rule {
+    id: "env-unwrap",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Environment and host calls fail for reasons the contract doesn't control; \
+                unwrapping them turns a recoverable error into a trap that reverts the call \
+                with no explanation.",
+    remediation: "Propagate the error with `?` or map it to a contract error the caller can see.",
+};
+
+pub const DYN_STORAGE: Rule = Rule {
+    id: "dyn-storage",
+    severity: Severity::High,
+    confidence: Confidence::High,
+    effort: Effort::Significant,
+    profiles: &["ink"],
+    rationale: "Trait objects have no fixed encoding and their vtables point into the code that \
+                created them, so `Box<dyn Trait>` can't be persisted in contract state \
+                deterministically.",
+    remediation: "Store a concrete type, or an enum with one variant per implementation.",
+};
+
+pub const CFG_DIVERGENCE: Rule = Rule {
+    id: "cfg-divergence",
+    severity: Severity::Info,
+    confidence: Confidence::High,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "Code behind `#[cfg(...)]` exists in only some builds, so a contract can be safe \
+                in the configuration that was reviewed and unsafe in the one that is deployed.",
+    remediation: "Review and test the function in every configuration it's compiled in, \
+                  especially `std` versus `no_std`.",
+};
+
+pub const RECEIVER_MUTATION: Rule = Rule {
+    id: "receiver-mutation",
+    severity: Severity::High,
+    confidence: Confidence::High,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Assigning to a field through `&self` doesn't compile, so its presence means the \
+                code was edited or expanded into something that was never built, or that state \
+                is being changed behind a shared reference.",
+    remediation: "Take `&mut self` in methods that write storage.",
+};
+
+pub const STD_COLLECTIONS: Rule = Rule {
+    id: "std-collections",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &["ink"],
+    rationale: "`std::collections` isn't available in `no_std` contract builds, and a `HashMap` \
+                in storage is encoded and loaded as a whole on every call instead of lazily per \
+                key like `Mapping`.",
+    remediation: "Use `ink::storage::Mapping` for storage and `ink::prelude::collections` for \
+                  in-memory collections.",
+};
+
+pub const UNTRUSTED_CALLBACK: Rule = Rule {
+    id: "untrusted-callback",
+    severity: Severity::High,
+    confidence: Confidence::Low,
+    effort: Effort::Significant,
+    profiles: &[],
+    rationale: "A callee account taken from a parameter and invoked later lets anyone point the \
+                contract at code they control, which can re-enter it mid-update or impersonate a \
+                trusted contract.",
+    remediation: "Only call accounts set by the owner or from an allowlist, and update state \
+                  before making the call.",
+};
+
+pub const LOST_ERROR: Rule = Rule {
+    id: "lost-error",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &["ink"],
+    rationale: "A message that returns `()` after calling a fallible helper reports success to \
+                the caller even when the helper failed, so the failure goes unnoticed.",
+    remediation: "Return the helper's `Result` from the message, or propagate it with `?`.",
+};
+
+pub const EMPTY_UNSAFE: Rule = Rule {
+    id: "empty-unsafe",
+    severity: Severity::Info,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "An `unsafe` block with nothing unsafe in it silences the compiler's checks for \
+                whatever is added to it later and sends reviewers looking for a hazard that \
+                isn't there.",
+    remediation: "Remove the `unsafe` block.",
+};
+
+pub const WRITE_ONLY_STATE: Rule = Rule {
+    id: "write-only-state",
+    severity: Severity::Info,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "Storage that is written but never read costs gas and storage deposit on every \
+                write for nothing, and often means the code that was meant to use it is missing.",
+    remediation: "Remove the field, or add the logic that reads it.",
+};
+
+pub const COMPILE_ENV: Rule = Rule {
+    id: "compile-env",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`env!` and `option_env!` copy environment variables from the machine that built \
+                the contract into its bytecode, which is public once deployed; secrets and \
+                host-specific paths end up on chain and builds stop being reproducible.",
+    remediation: "Pass configuration to the constructor or store it in contract state instead.",
+};
+
+pub const TRAIT_IMPL_MISMATCH: Rule = Rule {
+    id: "trait-impl-mismatch",
+    severity: Severity::Low,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &["ink"],
+    rationale: "ink! builds a trait's messages from the attributes on both the trait definition and \
+                its impl; a method marked `#[ink(message)]` on only one side is a half-finished \
+                edit that either fails to build or leaves the method out of the contract's ABI.",
+    remediation: "Give the impl method the same `#[ink(...)]` attributes as the trait method.",
+};
+
+pub const EARLY_RETURN: Rule = Rule {
+    id: "early-return",
+    severity: Severity::Low,
+    confidence: Confidence::Low,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "A conditional `return Ok(())` ahead of the function's storage updates tells the \
+                caller the operation succeeded while the transfer or state change it asked for \
+                never happened.",
+    remediation: "Return an error when the operation can't go ahead, or make sure the early \
+                  return really means there is nothing left to do.",
+};
+
+pub const TOPIC_MISUSE: Rule = Rule {
+    id: "topic-misuse",
+    severity: Severity::Medium,
+    confidence: Confidence::High,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "`#[ink(topic)]` only means something on the fields of an `#[ink(event)]` struct; \
+                anywhere else ink! rejects it, or the field was meant to be indexed on an event \
+                that doesn't carry it.",
+    remediation: "Remove the attribute, or move the field onto the event it should index.",
+};
+
+pub const NON_EXHAUSTIVE_MATCH: Rule = Rule {
+    id: "non-exhaustive-match",
+    severity: Severity::Info,
+    confidence: Confidence::Low,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "A `match` on caller input that lists every case by hand has no answer for values \
+                it didn't anticipate: integers outside the listed ones don't compile, and a \
+                `#[non_exhaustive]` enum that gains a variant breaks the contract on upgrade.",
+    remediation: "Add a `_ =>` arm that rejects unexpected input with an error.",
+};
+
+pub const NO_EVENTS: Rule = Rule {
+    id: "no-events",
+    severity: Severity::Info,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "Events are how wallets, explorers and indexers learn what a contract did; one that \
+                changes state without emitting any can only be followed by replaying every call.",
+    remediation: "Define `#[ink(event)]` structs for state changes and emit them from the \
+                  messages that make those changes.",
+};
+
+pub const LOOP_REMOVAL: Rule = Rule {
+    id: "loop-removal",
+    severity: Severity::Low,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "Removing from a `Vec` while looping over it by index shifts the remaining \
+                elements, so the loop skips the one after each removal or runs past the end, and \
+                every `remove` is itself O(n), making the loop quadratic in gas.",
+    remediation: "Filter with `retain`, or collect the indices to drop and remove them afterwards \
+                  in reverse order.",
+};
+
+pub const UNBOUNDED_STRING: Rule = Rule {
+    id: "unbounded-string",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "A `String` or `Vec<u8>` field set straight from a parameter grows to whatever \
+                the caller sends, bloating contract storage and making every later call that \
+                loads the field more expensive.",
+    remediation: "Reject input longer than a fixed maximum before storing it, or store a \
+                  bounded type such as a fixed-size array or hash.",
+};
+
+pub const PANIC_UNWRAP: Rule = Rule {
+    id: "panic-unwrap",
+    severity: Severity::Medium,
+    confidence: Confidence::High,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`unwrap` on a `None` or `Err` panics, which in a contract traps and reverts the \
+                whole call without telling the caller what went wrong.",
+    remediation: "Match on the value, use `unwrap_or`, or return an error with `?` / `ok_or`.",
+};
+
+pub const PANIC_EXPECT: Rule = Rule {
+    id: "panic-expect",
+    severity: Severity::Medium,
+    confidence: Confidence::High,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`expect` panics just like `unwrap`; its message ends up in the node's debug \
+                log, not in anything the caller receives, and the call still traps and reverts.",
+    remediation: "Return an error describing the failure instead, e.g. with `ok_or(Error::..)?`.",
+};
+
+pub const UNIT_MISMATCH: Rule = Rule {
+    id: "unit-mismatch",
+    severity: Severity::Low,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "Adding or comparing amounts named in different units (wei and tokens, seconds \
+                and milliseconds) is usually a missing conversion; the result is off by the \
+                conversion factor. Only the names are looked at, so this can be wrong.",
+    remediation: "Convert one operand to the other's unit before combining them, or rename the \
+                  variables if they actually share a unit.",
+};
+
+pub const EXPLICIT_PANIC: Rule = Rule {
+    id: "explicit-panic",
+    severity: Severity::High,
+    confidence: Confidence::High,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "`panic!`, `unreachable!`, `unimplemented!` and `todo!` compile without complaint \
+                but abort execution on chain, reverting the call with no error the caller can \
+                act on. `todo!` and `unimplemented!` usually mean the code path was never \
+                finished.",
+    remediation: "Return a contract error for conditions callers can trigger, and finish or \
+                  remove unimplemented paths before deploying.",
+};
+
+pub const SELF_CALL_GUARD: Rule = Rule {
+    id: "self-call-guard",
+    severity: Severity::Info,
+    confidence: Confidence::Low,
+    effort: Effort::Trivial,
+    profiles: &["ink"],
+    rationale: "Comparing the caller with the contract's own account only passes when the \
+                contract calls itself. That is right for some callbacks, but when an owner or \
+                admin check was meant, nobody outside can ever pass it.",
+    remediation: "Compare the caller with the stored owner or role instead, unless the message \
+                  really is only for self-calls; then say so in a comment.",
+};
+
+pub const ASSERT_PANIC: Rule = Rule {
+    id: "assert-panic",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "A failing `assert!`, `assert_eq!` or `assert_ne!` panics, trapping the call \
+                with no error the caller can match on.",
+    remediation: "Check the condition with `if` and return a contract error, e.g. \
+                  `ensure!`-style helpers that produce `Err(Error::..)`.",
+};
+
+pub const DEBUG_ASSERT: Rule = Rule {
+    id: "debug-assert",
+    severity: Severity::Low,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`debug_assert!` and its `_eq`/`_ne` forms are removed from release builds, so \
+                the deployed contract never performs the check; code after it may rely on a \
+                condition nothing enforces.",
+    remediation: "Keep `debug_assert!` for invariants only; validate anything callers control \
+                  with a check that returns an error.",
+};
+
+pub const UNCHECKED_ARITHMETIC: Rule = Rule {
+    id: "unchecked-arithmetic",
+    severity: Severity::High,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "`+`, `-` and `*` on caller-supplied integers wrap around silently in release \
+                builds without overflow checks and panic with them, so a large enough argument \
+                either corrupts balances or traps the call.",
+    remediation: "Use `checked_add`/`checked_sub`/`checked_mul` and return an error on `None`, \
+                  or `saturating_*` where clamping is the intended behavior.",
+};
+
+pub const PARAM_COUNT: Rule = Rule {
+    id: "param-count",
+    severity: Severity::Info,
+    confidence: Confidence::High,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "A function with many parameters is easy to call with two arguments swapped, \
+                especially when several share a type such as `AccountId` or `Balance`.",
+    remediation: "Group related parameters into a struct, or split the function.",
+};
+
+pub const UNCHECKED_INDEX: Rule = Rule {
+    id: "unchecked-index",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &[],
+    rationale: "Indexing with `[]` panics when the index is out of bounds, trapping the call; \
+                with a caller-controlled index anyone can make the message fail.",
+    remediation: "Use `.get(index)` and return an error on `None`, or check the index against \
+                  `.len()` first.",
+};
+
+pub const IGNORED_CALL_RESULT: Rule = Rule {
+    id: "ignored-call-result",
+    severity: Severity::High,
+    confidence: Confidence::Medium,
+    effort: Effort::Moderate,
+    profiles: &[],
+    rationale: "A cross-contract call can fail or revert in the callee; when its result is \
+                dropped the caller carries on as if it succeeded, e.g. crediting a transfer \
+                that never happened.",
+    remediation: "Propagate the result with `?` or match on it and handle the error case.",
+};
+
+pub const KEY_CONSISTENCY: Rule = Rule {
+    id: "key-consistency",
+    severity: Severity::Medium,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "A `Mapping` keyed by a tuple stores `(a, b)` and `(b, a)` as different entries; \
+                building the key in a different order in one place silently reads or writes \
+                the wrong entry, e.g. an allowance that never seems to be set.",
+    remediation: "Build the key in one helper function and use it everywhere the mapping is \
+                  accessed, or use a named struct as the key.",
+};
+
+pub const MISSING_ACCESS_CONTROL: Rule = Rule {
+    id: "missing-access-control",
+    severity: Severity::High,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "A `&mut self` message that never checks `self.env().caller()` lets any account \
+                change the contract's state through it, which is only right for messages meant \
+                to be public such as `transfer` of the caller's own funds.",
+    remediation: "Check the caller against the stored owner or role before mutating, or \
+                  document why the message is open to everyone.",
+};
+
+pub const COLLECT_STORAGE: Rule = Rule {
+    id: "collect-storage",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Significant,
+    profiles: &["ink"],
+    rationale: "Collecting a storage collection into a `Vec` reads and decodes every element, \
+                so the call's cost grows with the collection until it no longer fits in a \
+                block and the message can't be called at all.",
+    remediation: "Process elements as they are iterated, or take a bounded page with \
+                  `skip`/`take` and let callers ask for the next one.",
+};
+
+pub const UNINITIALIZED_FIELD: Rule = Rule {
+    id: "uninitialized-field",
+    severity: Severity::Medium,
+    confidence: Confidence::Medium,
+    effort: Effort::Trivial,
+    profiles: &["ink"],
+    rationale: "A storage field no constructor assigns starts from whatever its default \
+                encodes to, which usually means the contract was deployed with state its \
+                messages assume was set up, such as an empty balance map for the supply.",
+    remediation: "Assign the field in the constructor, or build the contract from \
+                  `Default::default()` if its default value is what's intended.",
+};
+
+pub const WIDTH_MISMATCH: Rule = Rule {
+    id: "width-mismatch",
+    severity: Severity::Low,
+    confidence: Confidence::Low,
+    effort: Effort::Moderate,
+    profiles: &["ink"],
+    rationale: "A parameter narrower or wider than the storage field it feeds needs a cast at \
+                the boundary; narrowing truncates silently and widening caps what callers can \
+                pass, and the other messages touching the field may assume a different width.",
+    remediation: "Take the parameter in the field's type, such as `Balance`, so the width is \
+                  decided once, or convert with `try_from` and handle the error.",
+};
+
+pub const UNSAFE_BLOCK: Rule = Rule {
+    id: "unsafe-block",
+    severity: Severity::High,
+    confidence: Confidence::High,
+    effort: Effort::Significant,
+    profiles: &[],
+    rationale: "Code in an `unsafe` block is exempt from the borrow checker and bounds checks, \
+                so a mistake in it can corrupt storage or memory rather than fail cleanly.",
+    remediation: "Replace the block with safe code, or keep it minimal and document the \
+                  invariants it relies on in a `// SAFETY:` comment.",
+};
+
+pub const RULES: &[&Rule] = &[
+    &EMPTY_COLLECTION_UNWRAP,
+    &STORAGE_LAYOUT,
+    &TIMESTAMP_EQUALITY,
+    &INVARIANT_ASSERT,
+    &EVENT_ORDERING,
+    &DUPLICATE_LITERAL,
+    &NO_ACCESS_CONTROL,
+    &UNSAFE_WITH_INPUT,
+    &MISSING_STORAGE_STRUCT,
+    &SIGN_CAST,
+    &STALE_CALLER,
+    &ERROR_SWALLOW,
+    &TIMING_ATTACK,
+    &ENV_UNWRAP,
+    &DYN_STORAGE,
+    &CFG_DIVERGENCE,
+    &RECEIVER_MUTATION,
+    &STD_COLLECTIONS,
+    &UNTRUSTED_CALLBACK,
+    &LOST_ERROR,
+    &EMPTY_UNSAFE,
+    &WRITE_ONLY_STATE,
+    &COMPILE_ENV,
+    &TRAIT_IMPL_MISMATCH,
+    &EARLY_RETURN,
+    &TOPIC_MISUSE,
+    &NON_EXHAUSTIVE_MATCH,
+    &NO_EVENTS,
+    &LOOP_REMOVAL,
+    &UNBOUNDED_STRING,
+    &PANIC_UNWRAP,
+    &UNIT_MISMATCH,
+    &PANIC_EXPECT,
+    &EXPLICIT_PANIC,
+    &SELF_CALL_GUARD,
+    &ASSERT_PANIC,
+    &DEBUG_ASSERT,
+    &UNCHECKED_ARITHMETIC,
+    &PARAM_COUNT,
+    &UNCHECKED_INDEX,
+    &IGNORED_CALL_RESULT,
+    &KEY_CONSISTENCY,
+    &MISSING_ACCESS_CONTROL,
+    &COLLECT_STORAGE,
+    &UNINITIALIZED_FIELD,
+    &WIDTH_MISMATCH,
+    &UNSAFE_BLOCK,
+];
+
+// Rules whose findings are panics, which abort the whole transaction under `panic = "abort"`;
+// unchecked arithmetic counts since contracts build with overflow checks on
+pub const PANIC_RULES: &[&Rule] = &[
+    &EMPTY_COLLECTION_UNWRAP,
+    &INVARIANT_ASSERT,
+    &ENV_UNWRAP,
+    &PANIC_UNWRAP,
+    &PANIC_EXPECT,
+    &EXPLICIT_PANIC,
+    &ASSERT_PANIC,
+    &UNCHECKED_INDEX,
+    &UNCHECKED_ARITHMETIC,
+];
+
+pub fn find(id: &str) -> Option<&'static Rule> {
+    RULES.iter().copied().find(|rule| rule.id == id)
+}
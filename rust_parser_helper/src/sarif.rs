@@ -0,0 +1,246 @@
+// SARIF 2.1.0 output, the format GitHub code scanning and most CI dashboards
+// ingest. Every built-in rule is listed under `tool.driver.rules` so results can
+// link to their rationale even when a run triggers only some of them.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::findings::Severity;
+use crate::rules::{self, Rule};
+use crate::ParseResult;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+// Key of `Finding::fingerprint` in `partialFingerprints`; the version changes
+// if the fingerprint's inputs ever do
+pub(crate) const FINGERPRINT_KEY: &str = "quardFingerprint/v2";
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Run {
+    pub tool: Tool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingDescriptor {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_description: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<Text>,
+    pub default_configuration: Configuration,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Configuration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Text {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Text,
+    pub locations: Vec<Location>,
+    // Lets code scanning match a result across runs even after its lines move
+    pub partial_fingerprints: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logical_locations: Vec<LogicalLocation>,
+}
+
+// The function or impl a finding is in, which baselines fingerprint it by
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalLocation {
+    pub fully_qualified_name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+pub fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium | Severity::Low => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn descriptor(rule: &Rule) -> ReportingDescriptor {
+    ReportingDescriptor {
+        id: rule.id.to_string(),
+        full_description: Some(Text {
+            text: rule.rationale.to_string(),
+        }),
+        help: Some(Text {
+            text: rule.remediation.to_string(),
+        }),
+        default_configuration: Configuration {
+            level: level(rule.severity),
+        },
+    }
+}
+
+// `file` is the path results are attributed to, as the scan was given it
+pub fn report(result: &ParseResult, file: &str) -> SarifLog {
+    let mut descriptors: Vec<ReportingDescriptor> =
+        rules::RULES.iter().map(|rule| descriptor(rule)).collect();
+    let uri = file.replace('\\', "/");
+
+    let mut results = Vec::new();
+    for finding in result.findings() {
+        // Plugin rules aren't in the built-in list, so they're described on first use
+        let rule_index = match descriptors.iter().position(|rule| rule.id == finding.rule_id) {
+            Some(index) => index,
+            None => {
+                descriptors.push(ReportingDescriptor {
+                    id: finding.rule_id.clone(),
+                    full_description: None,
+                    help: None,
+                    default_configuration: Configuration {
+                        level: level(finding.severity),
+                    },
+                });
+                descriptors.len() - 1
+            }
+        };
+        results.push(SarifResult {
+            rule_id: finding.rule_id.clone(),
+            rule_index,
+            level: level(finding.severity),
+            message: Text {
+                text: finding.message.clone(),
+            },
+            locations: vec![Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation { uri: uri.clone() },
+                    region: Region {
+                        start_line: finding.line_start,
+                        end_line: finding.line_end,
+                    },
+                },
+                logical_locations: finding
+                    .item
+                    .iter()
+                    .map(|item| LogicalLocation {
+                        fully_qualified_name: item.clone(),
+                    })
+                    .collect(),
+            }],
+            partial_fingerprints: BTreeMap::from([(FINGERPRINT_KEY, finding.fingerprint.clone())]),
+        });
+    }
+
+    SarifLog {
+        schema: SCHEMA,
+        version: "2.1.0",
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "rust_parser_helper",
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules: descriptors,
+                },
+            },
+            results,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    // The OASIS SARIF 2.1.0 schema, kept in the tree so the test runs offline. Its
+    // `language` pattern has a stray `]` that fails to compile as a regex; the copy
+    // here has it as `^[a-zA-Z]{2}(-[a-zA-Z]{2})?$`, the language tag it describes.
+    const SARIF_SCHEMA: &str = include_str!("../tests/fixtures/sarif-schema-2.1.0.json");
+    const SAMPLE: &str = include_str!("../../test_vulnerable_contract.rs");
+
+    fn sample_log() -> serde_json::Value {
+        let result = crate::parse_rust_source(SAMPLE, "lib.rs");
+        serde_json::to_value(report(&result, "contracts/lib.rs")).unwrap()
+    }
+
+    #[test]
+    fn sample_report_matches_the_sarif_schema() {
+        let schema: serde_json::Value = serde_json::from_str(SARIF_SCHEMA).unwrap();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        let log = sample_log();
+        let errors: Vec<String> = validator
+            .iter_errors(&log)
+            .map(|error| format!("{} at {}", error, error.instance_path()))
+            .collect();
+        assert!(errors.is_empty(), "{:#?}", errors);
+        assert!(!log["runs"][0]["results"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sample_unwrap_result_has_a_partial_fingerprint() {
+        let log = sample_log();
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        let unwrap = results.iter().find(|result| result["ruleId"] == "panic-unwrap").unwrap();
+        let fingerprint = unwrap["partialFingerprints"][FINGERPRINT_KEY].as_str().unwrap();
+        assert_eq!(fingerprint.len(), 16);
+
+        let fingerprints: HashSet<&str> = results
+            .iter()
+            .map(|result| result["partialFingerprints"][FINGERPRINT_KEY].as_str().unwrap())
+            .collect();
+        assert_eq!(fingerprints.len(), results.len());
+    }
+}
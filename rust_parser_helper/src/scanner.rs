@@ -0,0 +1,508 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use globset::{GlobBuilder, GlobMatcher};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::cache::FnCache;
+use crate::config::Config;
+use crate::findings::{Confidence, Severity};
+use crate::plugin::Plugin;
+use crate::{analyze_source_with, rules, AnalysisOptions, ParseResult};
+
+pub type ScanReport = ParseResult;
+
+// Contract type and profile of `build.rs` files, which only the generic rules apply to
+pub const BUILD_SCRIPT: &str = "build-script";
+
+pub fn is_build_script(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "build.rs")
+}
+
+// Configures which rules a `Scanner` runs and how their findings are reported
+#[derive(Debug, Clone, Default)]
+pub struct ScannerBuilder {
+    profile: Option<String>,
+    enabled: HashSet<String>,
+    disabled: HashSet<String>,
+    min_confidence: Option<Confidence>,
+    severity_overrides: HashMap<String, Severity>,
+    features: Vec<String>,
+    exclude_inactive_features: bool,
+    plugins: Vec<Arc<Plugin>>,
+    cache: Option<FnCache>,
+    panic_strategy: Option<String>,
+    jobs: Option<usize>,
+    max_params: Option<usize>,
+    privileged_markers: Vec<String>,
+    exclude: Vec<GlobMatcher>,
+    suppressions: Vec<(String, Option<GlobMatcher>)>,
+}
+
+impl ScannerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Overrides the profile detected from the source
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    // Applies a project config; settings made after this take precedence
+    pub fn config(mut self, config: &Config) -> Self {
+        if let Some(profile) = &config.profile {
+            self = self.profile(profile);
+        }
+        if let Some(confidence) = config.min_confidence {
+            self = self.min_confidence(confidence);
+        }
+        for id in &config.enable {
+            self = self.enable_rule(id);
+        }
+        for id in &config.disable {
+            self = self.disable_rule(id);
+        }
+        for (id, severity) in &config.severity {
+            self = self.severity_override(id, *severity);
+        }
+        for glob in &config.exclude {
+            self = self.exclude_path(glob);
+        }
+        for suppression in &config.suppress {
+            self = self.suppress(&suppression.rule, suppression.path.as_deref());
+        }
+        self
+    }
+
+    // Once any rule is enabled explicitly, only enabled rules run
+    pub fn enable_rule(mut self, id: impl Into<String>) -> Self {
+        self.enabled.insert(id.into());
+        self
+    }
+
+    pub fn disable_rule(mut self, id: impl Into<String>) -> Self {
+        self.disabled.insert(id.into());
+        self
+    }
+
+    pub fn min_confidence(mut self, confidence: Confidence) -> Self {
+        self.min_confidence = Some(confidence);
+        self
+    }
+
+    pub fn severity_override(mut self, id: impl Into<String>, severity: Severity) -> Self {
+        self.severity_overrides.insert(id.into(), severity);
+        self
+    }
+
+    // Cargo features treated as enabled for `#[cfg(feature = "...")]` gated code
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    // Drops findings in code gated behind a feature that isn't active
+    pub fn exclude_inactive_features(mut self, exclude: bool) -> Self {
+        self.exclude_inactive_features = exclude;
+        self
+    }
+
+    // Runs the plugin's rules alongside the built-in ones
+    pub fn plugin(mut self, plugin: Plugin) -> Self {
+        self.plugins.push(Arc::new(plugin));
+        self
+    }
+
+    // Keeps per-function results in `dir` and reuses them for unchanged functions
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache = Some(FnCache::new(dir));
+        self
+    }
+
+    // The crate's release `panic` setting; `"abort"` escalates panic findings
+    pub fn panic_strategy(mut self, strategy: Option<String>) -> Self {
+        self.panic_strategy = strategy;
+        self
+    }
+
+    // Caps the threads `scan_directory` parses files on; all cores by default
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
+    // Functions taking more parameters than this are flagged; `DEFAULT_MAX_PARAMS` otherwise
+    pub fn max_params(mut self, max: usize) -> Self {
+        self.max_params = Some(max);
+        self
+    }
+
+    // Treats fields and helpers whose name contains `marker` as privileged, in
+    // addition to `owner`, `admin` and the other built-in markers
+    pub fn privileged_marker(mut self, marker: impl Into<String>) -> Self {
+        self.privileged_markers.push(marker.into().to_lowercase());
+        self
+    }
+
+    // Skips files matching `glob`, relative to the root, when scanning a
+    // directory. A glob that doesn't compile excludes nothing.
+    pub fn exclude_path(mut self, glob: &str) -> Self {
+        self.exclude.extend(glob_matcher(glob));
+        self
+    }
+
+    // Drops `rule`'s findings, or only those in files matching `path`.
+    // A `path` that doesn't compile suppresses nothing.
+    pub fn suppress(mut self, rule: impl Into<String>, path: Option<&str>) -> Self {
+        match path.map(glob_matcher) {
+            Some(None) => {}
+            Some(matcher) => self.suppressions.push((rule.into(), matcher)),
+            None => self.suppressions.push((rule.into(), None)),
+        }
+        self
+    }
+
+    pub fn build(self) -> Scanner {
+        Scanner { config: self }
+    }
+}
+
+/// Scans source with the rules and reporting a [`ScannerBuilder`] configured.
+///
+/// ```
+/// use rust_parser_helper::scanner::Scanner;
+///
+/// let scanner = Scanner::builder().enable_rule("empty-collection-unwrap").build();
+/// let source = "fn f(v: &[u64]) -> bool { self.env().block_timestamp() == *v.first().unwrap() }";
+/// let report = scanner.scan_source(source);
+/// let rules: Vec<&str> = report.findings().map(|finding| finding.rule_id.as_str()).collect();
+/// assert_eq!(rules, ["empty-collection-unwrap"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Scanner {
+    config: ScannerBuilder,
+}
+
+impl Scanner {
+    pub fn builder() -> ScannerBuilder {
+        ScannerBuilder::new()
+    }
+
+    pub fn rule_enabled(&self, id: &str) -> bool {
+        let config = &self.config;
+        if config.disabled.contains(id) {
+            return false;
+        }
+        if !config.enabled.is_empty() && !config.enabled.contains(id) {
+            return false;
+        }
+        match (config.min_confidence, rules::find(id)) {
+            (Some(min), Some(rule)) => rule.confidence >= min,
+            _ => true,
+        }
+    }
+
+    // A path-scoped suppression never matches source scanned without a path
+    fn suppressed(&self, rule_id: &str, path: Option<&Path>) -> bool {
+        self.config.suppressions.iter().any(|(rule, glob)| {
+            rule == rule_id
+                && match glob {
+                    Some(glob) => path.is_some_and(|path| glob.is_match(path)),
+                    None => true,
+                }
+        })
+    }
+
+    // Severity a rule's findings end up with once overrides and the panic strategy apply
+    fn effective_severity(&self, rule: &rules::Rule) -> Severity {
+        if let Some(severity) = self.config.severity_overrides.get(rule.id) {
+            return *severity;
+        }
+        let aborts = self.config.panic_strategy.as_deref() == Some("abort");
+        if aborts && rules::PANIC_RULES.iter().any(|panic_rule| panic_rule.id == rule.id) {
+            return rule.severity.escalated();
+        }
+        rule.severity
+    }
+
+    // sha256 over the rules that run under `profile`, with their effective
+    // severity and confidence, so consumers can tell when the configuration drifted
+    pub fn ruleset_hash(&self, profile: &str) -> String {
+        let mut hasher = Sha256::new();
+        for rule in rules::RULES {
+            if !rule.applies_to(profile) || !self.rule_enabled(rule.id) {
+                continue;
+            }
+            let severity = self.effective_severity(rule);
+            hasher.update(format!("{} {:?} {:?}\n", rule.id, severity, rule.confidence));
+        }
+        for rule in self.config.plugins.iter().flat_map(|plugin| plugin.rules()) {
+            if self.rule_enabled(rule.id()) {
+                hasher.update(format!("{} plugin\n", rule.id()));
+            }
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    pub fn scan_source(&self, source: &str) -> ScanReport {
+        self.scan(source, None)
+    }
+
+    // Like `scan_source`, but a `build.rs` runs at build time rather than on
+    // chain, so it's reported as a build script and contract rules skip it.
+    // `path` also names the file in a parse error and is what suppressions match.
+    pub fn scan_file(&self, path: &Path, source: &str) -> ScanReport {
+        self.scan(source, Some(path))
+    }
+
+    // Every .rs file under `root`, with its path relative to `root`, sorted by path.
+    // Files matched by a .gitignore, hidden directories and `target/` are skipped.
+    pub fn scan_directory(&self, root: &Path) -> io::Result<Vec<(PathBuf, ScanReport)>> {
+        let paths: Vec<PathBuf> = rust_files(root)?
+            .into_iter()
+            .map(|path| path.strip_prefix(root).map(Path::to_path_buf).unwrap_or(path))
+            .filter(|relative| !self.config.exclude.iter().any(|glob| glob.is_match(relative)))
+            .collect();
+        let scan_all = || {
+            paths
+                .par_iter()
+                .map(|relative| {
+                    let source = fs::read_to_string(root.join(relative))?;
+                    let report = self.scan_file(relative, &source);
+                    Ok((relative.clone(), report))
+                })
+                .collect::<io::Result<Vec<_>>>()
+        };
+
+        let mut results = match self.config.jobs {
+            Some(jobs) => rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .map_err(io::Error::other)?
+                .install(scan_all)?,
+            None => scan_all()?,
+        };
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(results)
+    }
+
+    fn scan(&self, source: &str, path: Option<&Path>) -> ScanReport {
+        let name = path.map_or_else(|| "<source>".to_string(), |path| path.display().to_string());
+        let kind = path.filter(|path| is_build_script(path)).map(|_| BUILD_SCRIPT);
+        let mut options = AnalysisOptions {
+            cache: self.config.cache.clone(),
+            ..AnalysisOptions::default()
+        };
+        if let Some(max) = self.config.max_params {
+            options.max_params = max;
+        }
+        options.privileged_markers.extend(self.config.privileged_markers.iter().cloned());
+        let mut report = analyze_source_with(source, &name, options);
+        for rule in self.config.plugins.iter().flat_map(|plugin| plugin.rules()) {
+            report.plugin_warnings.extend(rule.check(source));
+        }
+        report.assign_fingerprints();
+        if let Some(profile) = &self.config.profile {
+            report.profile = profile.clone();
+        }
+        if let Some(kind) = kind {
+            report.contract_type = kind.to_string();
+            report.profile = kind.to_string();
+        }
+
+        report.active_features = self.config.features.clone();
+
+        report.apply_profile();
+        report.retain_findings(|finding| self.rule_enabled(&finding.rule_id));
+        report.retain_findings(|finding| !self.suppressed(&finding.rule_id, path));
+        if self.config.exclude_inactive_features {
+            let active = &self.config.features;
+            report.retain_findings(|finding| {
+                finding.feature.as_ref().is_none_or(|feature| active.contains(feature))
+            });
+        }
+        report.panic_strategy = self.config.panic_strategy.clone();
+        if report.panic_strategy.as_deref() == Some("abort") {
+            for finding in report.findings_mut() {
+                if rules::PANIC_RULES.iter().any(|rule| rule.id == finding.rule_id) {
+                    finding.severity = finding.severity.escalated();
+                }
+            }
+        }
+        for finding in report.findings_mut() {
+            if let Some(severity) = self.config.severity_overrides.get(&finding.rule_id) {
+                finding.severity = *severity;
+            }
+        }
+        report.ruleset_hash = self.ruleset_hash(&report.profile);
+        report.collect_findings();
+
+        report
+    }
+}
+
+// Globs match whole path components, as in .gitignore: `*` stops at `/` and `**` crosses it
+fn glob_matcher(glob: &str) -> Option<GlobMatcher> {
+    let glob = GlobBuilder::new(glob).literal_separator(true).build().ok()?;
+    Some(glob.compile_matcher())
+}
+
+fn rust_files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let walker = ignore::WalkBuilder::new(root)
+        // Honor .gitignore even when the tree isn't a git checkout
+        .require_git(false)
+        .filter_entry(|entry| entry.file_name() != "target")
+        .build();
+
+    let mut paths = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(io::Error::other)?;
+        let path = entry.path();
+        if entry.file_type().is_some_and(|kind| kind.is_file())
+            && path.extension().is_some_and(|extension| extension == "rs")
+        {
+            paths.push(path.to_path_buf());
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inactive_feature_findings_are_excluded_on_request() {
+        let source = "#[cfg(feature = \"std\")]\nfn f(v: Vec<u8>) -> u8 { *v.first().unwrap() }\n";
+        let count = |scanner: Scanner| scanner.scan_source(source).empty_collection_warnings.len();
+
+        assert_eq!(count(Scanner::builder().exclude_inactive_features(true).build()), 0);
+        let active = Scanner::builder().features(["std"]).exclude_inactive_features(true);
+        assert_eq!(count(active.build()), 1);
+        assert_eq!(count(Scanner::builder().build()), 1);
+    }
+
+    #[test]
+    fn abort_panic_strategy_escalates_panic_findings() {
+        let source = "fn f(v: &[u8]) -> u8 { *v.first().unwrap() }\n\
+                      fn g(a: u8, b: u8) -> u8 { a + b }\n";
+        let severities = |strategy: Option<&str>| {
+            let scanner = Scanner::builder().panic_strategy(strategy.map(String::from)).build();
+            let report = scanner.scan_source(source);
+            (
+                report.empty_collection_warnings[0].severity,
+                report.unchecked_arithmetic_warnings[0].severity,
+            )
+        };
+        assert_eq!(severities(None), (Severity::Medium, Severity::High));
+        assert_eq!(severities(Some("unwind")), (Severity::Medium, Severity::High));
+        assert_eq!(severities(Some("abort")), (Severity::High, Severity::Critical));
+    }
+
+    #[test]
+    fn build_script_skips_contract_rules() {
+        let source = "#[ink::contract]\nmod c {\n    #[ink(storage)]\n    pub struct C {\n        \
+                      shared: std::rc::Rc<u8>,\n    }\n}\n";
+        let scanner = Scanner::builder().build();
+
+        let build = scanner.scan_file(Path::new("build.rs"), source);
+        assert_eq!(build.contract_type, BUILD_SCRIPT);
+        assert_eq!(build.profile, BUILD_SCRIPT);
+        assert!(build.storage_layout_warnings.is_empty());
+        let lib = scanner.scan_file(Path::new("lib.rs"), source);
+        assert_eq!(lib.storage_layout_warnings.len(), 1);
+    }
+
+    #[test]
+    fn scan_directory_skips_ignored_hidden_and_build_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let files = ["src/lib.rs", "src/nested/mod.rs", "gen/out.rs", ".cache/x.rs", "target/y.rs"];
+        for path in files {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), "fn f() {}\n").unwrap();
+        }
+        fs::write(root.join("src/notes.txt"), "not rust\n").unwrap();
+        fs::write(root.join(".gitignore"), "gen/\n").unwrap();
+
+        let results = Scanner::builder().build().scan_directory(root).unwrap();
+        let paths: Vec<&Path> = results.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(paths, [Path::new("src/lib.rs"), Path::new("src/nested/mod.rs")]);
+    }
+
+    #[test]
+    fn parallel_directory_scan_matches_a_single_job() {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 0..8 {
+            let source = format!("fn f{}(v: &[u8]) -> u8 {{ *v.first().unwrap() }}\n", index);
+            fs::write(dir.path().join(format!("f{}.rs", index)), source).unwrap();
+        }
+
+        let summary = |scanner: Scanner| -> Vec<(PathBuf, Vec<String>)> {
+            let results = scanner.scan_directory(dir.path()).unwrap();
+            results
+                .into_iter()
+                .map(|(path, report)| {
+                    let fingerprints = report.findings().map(|f| f.fingerprint.clone());
+                    (path, fingerprints.collect())
+                })
+                .collect()
+        };
+        let parallel = summary(Scanner::builder().jobs(4).build());
+        assert_eq!(parallel.len(), 8);
+        assert_eq!(parallel, summary(Scanner::builder().jobs(1).build()));
+    }
+
+    #[test]
+    fn max_params_sets_the_parameter_threshold() {
+        let source = "fn f(a: u8, b: u8, c: u8) {}\n";
+        let count = |scanner: Scanner| scanner.scan_source(source).param_count_warnings.len();
+        assert_eq!(count(Scanner::builder().build()), 0);
+        assert_eq!(count(Scanner::builder().max_params(2).build()), 1);
+    }
+
+    #[test]
+    fn unparsable_file_is_named_and_typed_like_detect_only() {
+        let source = "#[ink::contract]\nmod c {\n    fn broken( {\n}\n";
+        let report = Scanner::builder().build().scan_file(Path::new("src/lib.rs"), source);
+        assert_eq!(report.contract_type, crate::detect_contract_type(source));
+        assert_eq!(report.contract_type, "ink");
+        let error = &report.errors[0];
+        assert!(error.starts_with("Parse error in src/lib.rs:3:"), "{}", error);
+    }
+
+    #[test]
+    fn config_excludes_files_and_suppresses_findings_by_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let unwrap = "fn f(v: &[u8]) -> u8 { *v.first().unwrap() }\n";
+        for file in ["src/lib.rs", "src/legacy/old.rs", "tests/it.rs"] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, unwrap).unwrap();
+        }
+        let config = crate::config::load(
+            "exclude = [\"tests/**\"]\n[[suppress]]\nrule = \"empty-collection-unwrap\"\n\
+             path = \"src/legacy/*.rs\"\n",
+        )
+        .unwrap();
+
+        let results = Scanner::builder().config(&config).build().scan_directory(dir.path());
+        let counts: Vec<(String, usize)> = results
+            .unwrap()
+            .iter()
+            .map(|(path, report)| (path.display().to_string(), report.findings().count()))
+            .collect();
+        let expected = [("src/legacy/old.rs", 0), ("src/lib.rs", 1)];
+        assert_eq!(counts, expected.map(|(path, count)| (path.to_string(), count)));
+    }
+}
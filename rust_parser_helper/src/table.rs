@@ -0,0 +1,97 @@
+// Human-readable findings table, fitted to the terminal so long messages are
+// cut with an ellipsis instead of wrapping into unreadable rows.
+
+use crate::ParseResult;
+
+// Used when stdout isn't a terminal, such as when piped into a file or pager
+pub const FALLBACK_WIDTH: usize = 100;
+
+const SEVERITY_WIDTH: usize = 8;
+const MAX_RULE_WIDTH: usize = 24;
+const MIN_MESSAGE_WIDTH: usize = 10;
+const GAP: &str = "  ";
+
+// The width of the terminal stdout is attached to, if any
+pub fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| width as usize)
+        .unwrap_or(FALLBACK_WIDTH)
+}
+
+pub fn render(result: &ParseResult, width: usize) -> String {
+    let rows: Vec<[String; 4]> = result
+        .findings()
+        .map(|finding| {
+            let lines = if finding.line_end > finding.line_start {
+                format!("{}-{}", finding.line_start, finding.line_end)
+            } else {
+                finding.line_start.to_string()
+            };
+            [
+                format!("{:?}", finding.severity).to_lowercase(),
+                finding.rule_id.clone(),
+                lines,
+                finding.message.clone(),
+            ]
+        })
+        .collect();
+    if rows.is_empty() {
+        return "No findings.\n".to_string();
+    }
+
+    let column = |index: usize, header: &str| {
+        rows.iter()
+            .map(|row| row[index].chars().count())
+            .chain([header.chars().count()])
+            .max()
+            .unwrap_or(0)
+    };
+    let widths = [
+        SEVERITY_WIDTH,
+        column(1, "RULE").min(MAX_RULE_WIDTH),
+        column(2, "LINES"),
+    ];
+    let fixed: usize = widths.iter().sum::<usize>() + GAP.len() * widths.len();
+    let message_width = width.saturating_sub(fixed).max(MIN_MESSAGE_WIDTH);
+
+    let mut out = String::new();
+    let header = ["SEVERITY", "RULE", "LINES", "MESSAGE"].map(String::from);
+    for row in std::iter::once(&header).chain(&rows) {
+        for (cell, width) in row.iter().zip(widths) {
+            out.push_str(&format!("{:<width$}{}", truncate(cell, width), GAP, width = width));
+        }
+        out.push_str(truncate(&row[3], message_width).trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// Cuts `text` to at most `width` characters, ending in `…` when anything was dropped
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_messages_are_cut_to_the_width() {
+        let source = "fn f(v: &[u8]) -> u8 { *v.first().unwrap() }\n\
+                      fn g(n: i64) -> u64 { n as u64 }";
+        let table = render(&crate::parse_rust_source(source, "test.rs"), 60);
+        assert_eq!(table.lines().count(), 3, "{}", table);
+        assert!(table.lines().all(|line| line.chars().count() <= 60), "{}", table);
+        assert!(table.lines().skip(1).all(|line| line.ends_with('…')), "{}", table);
+    }
+
+    #[test]
+    fn truncate_keeps_text_that_fits() {
+        assert_eq!(truncate("reentrancy", 10), "reentrancy");
+        assert_eq!(truncate("reentrancy", 6), "reent…");
+    }
+}
@@ -0,0 +1,178 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
+
+// Operators rendered with surrounding spaces when used in binary position
+const SPACED_OPERATORS: &[&str] = &[
+    "=", "==", "!=", "<", ">", "<=", ">=", "+", "-", "*", "/", "%", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "=>", "->", "|", "^",
+];
+
+#[derive(PartialEq)]
+enum Last {
+    Start,
+    Word,
+    Operator,
+    Close,
+}
+
+struct Writer {
+    out: String,
+    last: Last,
+    // In type position every `<`/`>` delimits generic arguments
+    types: bool,
+    // Open `<` of a turbofish or generic argument list
+    angle_depth: usize,
+    // Between the `|`s of a closure's parameter list
+    closure_params: bool,
+}
+
+// Renders a syntax node the way it would be written by hand, rather than
+// quote's token-per-space output (`self . balances . get (& account)`).
+pub fn snippet(node: &impl ToTokens) -> String {
+    render(node, false)
+}
+
+// Like `snippet`, but keeps generic brackets tight: `Mapping<AccountId, Balance>`
+pub fn type_string(node: &impl ToTokens) -> String {
+    render(node, true)
+}
+
+fn render(node: &impl ToTokens, types: bool) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        last: Last::Start,
+        types,
+        angle_depth: 0,
+        closure_params: false,
+    };
+    writer.write_stream(node.to_token_stream());
+    writer.out
+}
+
+impl Writer {
+    fn write_stream(&mut self, stream: TokenStream) {
+        let mut operator = String::new();
+
+        for token in stream {
+            match token {
+                TokenTree::Punct(punct) => {
+                    operator.push(punct.as_char());
+                    if punct.spacing() == Spacing::Joint {
+                        continue;
+                    }
+                    self.write_operator(&operator);
+                    operator.clear();
+                }
+                TokenTree::Ident(ident) => {
+                    self.write_word(&ident.to_string(), &mut operator);
+                }
+                TokenTree::Literal(literal) => {
+                    self.write_word(&literal.to_string(), &mut operator);
+                }
+                TokenTree::Group(group) => {
+                    if !operator.is_empty() {
+                        self.write_operator(&operator);
+                        operator.clear();
+                    }
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{ ", " }"),
+                        Delimiter::None => ("", ""),
+                    };
+                    if group.delimiter() == Delimiter::Brace
+                        && self.last != Last::Start
+                        && !self.out.ends_with(' ')
+                    {
+                        self.out.push(' ');
+                    }
+                    self.out.push_str(open);
+                    self.last = Last::Start;
+                    self.write_stream(group.stream());
+                    if self.out.ends_with(' ') {
+                        self.out.push_str(close.trim_start());
+                    } else {
+                        self.out.push_str(close);
+                    }
+                    self.last = Last::Close;
+                }
+            }
+        }
+
+        if !operator.is_empty() {
+            self.write_operator(&operator);
+        }
+    }
+
+    fn write_word(&mut self, word: &str, pending: &mut String) {
+        // A joint `'` is the start of a lifetime such as `'a`
+        if pending == "'" {
+            if self.last == Last::Word {
+                self.out.push(' ');
+            }
+            self.out.push('\'');
+            pending.clear();
+        } else if matches!(self.last, Last::Word | Last::Close) {
+            self.out.push(' ');
+        }
+        self.out.push_str(word);
+        self.last = Last::Word;
+    }
+
+    fn write_operator(&mut self, operator: &str) {
+        let binary = matches!(self.last, Last::Word | Last::Close)
+            && self.out.rsplit(|c: char| !c.is_alphanumeric() && c != '_').next() != Some("move");
+
+        if operator == "|" && (self.closure_params || !binary) {
+            // `|x| x + 1`: tight inside the parameter list, spaced before the body
+            if self.last == Last::Word && !self.closure_params {
+                self.out.push(' ');
+            }
+            self.out.push('|');
+            if self.closure_params {
+                self.out.push(' ');
+            }
+            self.closure_params = !self.closure_params;
+        } else if operator == "||" && !binary {
+            self.out.push_str("|| ");
+        } else if operator == "<" && (self.types || self.angle_depth > 0)
+            || operator.ends_with('<') && !binary
+        {
+            // Inside a turbofish every further `<` opens nested generic arguments too
+            self.angle_depth += 1;
+            self.out.push_str(operator);
+        } else if operator.starts_with('>') && self.angle_depth > 0 {
+            self.angle_depth -= operator.len().min(self.angle_depth);
+            self.out.push_str(operator);
+            self.last = Last::Close;
+            return;
+        } else if binary && SPACED_OPERATORS.contains(&operator) {
+            self.out.push(' ');
+            self.out.push_str(operator);
+            self.out.push(' ');
+        } else if operator == "," || operator == ";" || operator == ":" {
+            self.out.push_str(operator);
+            self.out.push(' ');
+        } else {
+            self.out.push_str(operator);
+        }
+        self.last = Last::Operator;
+    }
+}
+
+// Every identifier in a node, including those nested in groups and macro bodies
+pub fn idents(node: &impl ToTokens) -> Vec<String> {
+    fn collect(stream: TokenStream, out: &mut Vec<String>) {
+        for token in stream {
+            match token {
+                TokenTree::Ident(ident) => out.push(ident.to_string()),
+                TokenTree::Group(group) => collect(group.stream(), out),
+                _ => {}
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(node.to_token_stream(), &mut out);
+    out
+}
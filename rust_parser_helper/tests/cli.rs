@@ -0,0 +1,280 @@
+// End-to-end runs of the binary: flags, exit codes and output formats.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+const SAMPLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../test_vulnerable_contract.rs");
+
+// Runs in `dir` so a config file in the crate's own directory can't leak in
+fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust_parser_helper"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+fn run(args: &[&str]) -> Output {
+    run_in(&std::env::temp_dir(), args)
+}
+
+fn stdout_json(output: &Output) -> Value {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn profile_from_manifest_follows_dependencies_over_source() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"c\"\n\n[dependencies]\nnear-sdk = \"5\"\n",
+    )
+    .unwrap();
+    let source = dir.path().join("lib.rs");
+    fs::copy(SAMPLE, &source).unwrap();
+
+    let report = stdout_json(&run(&[source.to_str().unwrap(), "--profile-from-manifest"]));
+    assert_eq!(report["profile"], "near");
+    assert_eq!(report["contract_type"], "ink");
+}
+
+#[test]
+fn explain_finding_shows_the_snippet_and_remediation() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("c.rs");
+    fs::write(&source, "fn f(items: Vec<u8>) -> u8 { *items.first().unwrap() }\n").unwrap();
+    let source = source.to_str().unwrap();
+    let report = stdout_json(&run(&[source]));
+    let fingerprint = report["empty_collection_warnings"][0]["fingerprint"].as_str().unwrap();
+
+    let output = run(&[source, "--explain-finding", fingerprint]);
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains(">     1 | fn f(items: Vec<u8>) -> u8 {"), "{}", text);
+    assert!(text.contains("Bind the element with `if let Some(x) = v.first()`"), "{}", text);
+}
+
+#[test]
+fn detect_only_prints_just_the_contract_type() {
+    let output = run(&[SAMPLE, "--detect-only"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "ink\n");
+}
+
+#[test]
+fn bench_over_a_tiny_corpus_reports_timings() {
+    let corpus = tempfile::tempdir().unwrap();
+    fs::write(corpus.path().join("a.rs"), "fn f() {}\n").unwrap();
+
+    let args = ["bench", "--iterations", "2", "--corpus", corpus.path().to_str().unwrap()];
+    let report = stdout_json(&run(&args));
+    assert_eq!(report["files"], 1);
+    assert_eq!(report["iterations"], 2);
+    for key in ["mean_ms", "median_ms", "p95_ms", "min_ms", "max_ms"] {
+        assert!(report[key].as_f64().is_some_and(|ms| ms >= 0.0), "{}", key);
+    }
+}
+
+#[test]
+fn config_validate_fails_on_an_unknown_rule() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("quard.toml");
+    fs::write(&config, "disable = [\"no-such-rule\"]\n").unwrap();
+
+    let output = run(&["--config-validate", config.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(":1: unknown rule id `no-such-rule`"), "{}", stderr);
+}
+
+#[test]
+fn severity_summary_matches_the_detailed_findings() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("c.rs");
+    fs::write(
+        &source,
+        "fn a(v: &[u8]) -> u8 { *v.first().unwrap() }\n\
+         fn b(&self, t: u64) -> bool { self.env().block_timestamp() == t }\n\
+         fn c(n: i64) -> u64 { n as u64 }\n",
+    )
+    .unwrap();
+    let source = source.to_str().unwrap();
+
+    let detailed = stdout_json(&run(&[source]));
+    let findings: Vec<&Value> = detailed
+        .as_object()
+        .unwrap()
+        .iter()
+        .filter(|(key, _)| key.ends_with("_warnings"))
+        .flat_map(|(_, list)| list.as_array().unwrap())
+        .collect();
+    assert_eq!(findings.len(), 3);
+    let summary = stdout_json(&run(&[source, "--severity-summary-only"]));
+
+    for severity in ["critical", "high", "medium", "low", "info"] {
+        let count = findings.iter().filter(|finding| finding["severity"] == severity).count();
+        assert_eq!(summary[severity], count, "{}", severity);
+    }
+    assert_eq!(summary["total"], findings.len());
+}
+
+#[test]
+fn baseline_update_drops_resolved_findings() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("c.rs");
+    let baseline = dir.path().join("baseline.json");
+    let baseline_arg = baseline.to_str().unwrap();
+    let unwraps = |report: &Value| report["empty_collection_warnings"].as_array().unwrap().len();
+
+    let a = "fn a(v: &[u8]) -> u8 { *v.first().unwrap() }\n";
+    fs::write(&source, format!("{}fn b(v: &[u8]) -> u8 {{ *v.last().unwrap() }}\n", a)).unwrap();
+    let first = stdout_json(&run(&[source.to_str().unwrap(), "--baseline-update", baseline_arg]));
+    assert_eq!(unwraps(&first), 2);
+
+    fs::write(&source, a).unwrap();
+    let second = stdout_json(&run(&[source.to_str().unwrap(), "--baseline-update", baseline_arg]));
+    assert_eq!(unwraps(&second), 0);
+    let updated: Value = serde_json::from_str(&fs::read_to_string(&baseline).unwrap()).unwrap();
+    assert_eq!(unwraps(&updated), 1);
+}
+
+#[test]
+fn ruleset_hash_follows_the_enabled_rules() {
+    let hash = |args: &[&str]| {
+        let report = stdout_json(&run(&[&[SAMPLE], args].concat()));
+        report["ruleset_hash"].as_str().unwrap().to_string()
+    };
+    let default = hash(&[]);
+    assert_eq!(default.len(), 64);
+    assert_eq!(hash(&[]), default);
+
+    let denied = hash(&["--deny", "empty-collection-unwrap"]);
+    let only = hash(&["--only", "empty-collection-unwrap"]);
+    assert_ne!(denied, default);
+    assert_ne!(only, default);
+    assert_ne!(only, denied);
+}
+
+#[test]
+fn indent_sets_the_json_indentation() {
+    let lines = |args: &[&str]| {
+        let output = run(&[&[SAMPLE], args].concat());
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().lines().take(3).collect::<Vec<_>>().join("\n")
+    };
+    assert_eq!(lines(&["--indent", "4"]), "{\n    \"functions\": [],\n    \"structs\": [");
+    assert_eq!(lines(&["--indent", "tab"]), "{\n\t\"functions\": [],\n\t\"structs\": [");
+    assert_eq!(lines(&[]), "{\n  \"functions\": [],\n  \"structs\": [");
+
+    let output = run(&[SAMPLE, "--indent", "wide"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("expected a number of spaces or `tab`, got `wide`"), "{}", stderr);
+}
+
+#[test]
+fn source_flag_scans_the_given_code() {
+    let report = stdout_json(&run(&["--source", "fn f(){ x.unwrap(); }"]));
+    let findings = report["findings"].as_array().unwrap();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0]["rule_id"], "panic-unwrap");
+    assert_eq!(findings[0]["line_start"], 1);
+}
+
+#[test]
+fn broken_file_fails_a_directory_scan_only_under_the_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("good.rs"), "fn ok() {}\n").unwrap();
+    fs::write(dir.path().join("broken.rs"), "fn broken( {\n").unwrap();
+    let dir_arg = dir.path().to_str().unwrap();
+
+    let lenient = run(&["--dir", dir_arg]);
+    assert!(lenient.status.success());
+
+    let strict = run(&["--dir", dir_arg, "--fail-on-parse-error"]);
+    assert_eq!(strict.status.code(), Some(1));
+    assert!(!strict.stdout.is_empty());
+    let stderr = String::from_utf8(strict.stderr).unwrap();
+    assert!(stderr.starts_with("broken.rs: Parse error"), "{}", stderr);
+}
+
+#[test]
+fn config_in_the_working_directory_disables_a_rule() {
+    let rule_ids = |report: &Value| -> Vec<String> {
+        let findings = report["findings"].as_array().unwrap();
+        findings.iter().map(|finding| finding["rule_id"].as_str().unwrap().to_string()).collect()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let unconfigured = rule_ids(&stdout_json(&run_in(dir.path(), &[SAMPLE])));
+    assert!(unconfigured.iter().any(|id| id == "panic-unwrap"));
+
+    for config in ["disable = [\"panic-unwrap\"]\n", "[rules]\npanic-unwrap = false\n"] {
+        fs::write(dir.path().join("quard.toml"), config).unwrap();
+        let ids = rule_ids(&stdout_json(&run_in(dir.path(), &[SAMPLE])));
+        assert!(!ids.is_empty());
+        assert!(!ids.iter().any(|id| id == "panic-unwrap"), "{}: {:?}", config, ids);
+    }
+}
+
+#[test]
+fn exit_status_follows_the_documented_contract() {
+    // The sample's worst findings are high
+    assert_eq!(run(&[SAMPLE]).status.code(), Some(0));
+    assert_eq!(run(&[SAMPLE, "--fail-on", "critical"]).status.code(), Some(0));
+
+    let failed = run(&[SAMPLE, "--fail-on", "high"]);
+    assert_eq!(failed.status.code(), Some(2));
+    let report: Value = serde_json::from_slice(&failed.stdout).unwrap();
+    assert!(report["findings"].as_array().is_some_and(|findings| !findings.is_empty()));
+
+    assert_eq!(run(&["/nonexistent/contract.rs"]).status.code(), Some(1));
+    assert_eq!(run(&[SAMPLE, "--fail-on", "severe"]).status.code(), Some(1));
+
+    let help = String::from_utf8(run(&["--help"]).stdout).unwrap();
+    assert!(help.contains("Exit status: 0 when the scan completes"), "{}", help);
+}
+
+#[test]
+fn doctor_reports_each_capability_as_a_boolean() {
+    let report = stdout_json(&run(&["doctor"]));
+    let capabilities = ["cargo_expand", "git", "tree_sitter", "span_locations"];
+    for capability in capabilities {
+        assert!(report[capability].is_boolean(), "{}: {}", capability, report);
+    }
+    // This crate is always built with span-locations on
+    assert_eq!(report["span_locations"], true);
+    assert_eq!(report["plugin_abi_version"], rust_parser_helper::plugin::ABI_VERSION);
+}
+
+#[test]
+fn directory_baseline_only_suppresses_findings_in_the_same_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    fs::create_dir(&src).unwrap();
+    let unwrap = "fn a(v: &[u8]) -> u8 { *v.first().unwrap() }\n";
+    fs::write(src.join("old.rs"), unwrap).unwrap();
+    let src_arg = src.to_str().unwrap();
+    let baseline = dir.path().join("baseline.json");
+    fs::write(&baseline, run(&["--dir", src_arg]).stdout).unwrap();
+
+    // Same code, so the same fingerprint, but in a file the baseline doesn't cover
+    fs::write(src.join("new.rs"), unwrap).unwrap();
+    let scan = stdout_json(&run(&["--dir", src_arg, "--baseline", baseline.to_str().unwrap()]));
+    let counts: Vec<(&str, usize)> = scan
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| {
+            let findings = file["report"]["findings"].as_array().unwrap();
+            (file["path"].as_str().unwrap(), findings.len())
+        })
+        .collect();
+    assert_eq!(counts, [("new.rs", 1), ("old.rs", 0)]);
+
+    let conflict = run(&["--dir", src_arg, "--severity-summary-only"]);
+    assert!(!conflict.status.success());
+}
@@ -0,0 +1,93 @@
+// Builds a plugin cdylib against this crate and runs its rule through a scan.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use rust_parser_helper::plugin::Plugin;
+use rust_parser_helper::scanner::Scanner;
+
+const PLUGIN_MANIFEST: &str = r#"
+[package]
+name = "todo_plugin"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+rust_parser_helper = { path = "HOST" }
+
+[workspace]
+"#;
+
+const PLUGIN_SOURCE: &str = r#"
+use rust_parser_helper::findings::{Finding, Severity};
+use rust_parser_helper::plugin::PluginRule;
+
+struct TodoComment;
+
+impl PluginRule for TodoComment {
+    fn id(&self) -> &str {
+        "todo-comment"
+    }
+
+    fn check(&self, source: &str) -> Vec<Finding> {
+        source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.contains("TODO"))
+            .map(|(index, _)| {
+                let message = "unfinished work left in the contract".to_string();
+                Finding::new(self.id(), message, Severity::Low, index + 1, index + 1)
+            })
+            .collect()
+    }
+}
+
+rust_parser_helper::declare_plugin!(TodoComment);
+"#;
+
+// Kept under the test target directory so later runs only rebuild what changed
+fn build_plugin() -> PathBuf {
+    let host = env!("CARGO_MANIFEST_DIR");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("todo_plugin");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), PLUGIN_MANIFEST.replace("HOST", host)).unwrap();
+    fs::write(dir.join("src/lib.rs"), PLUGIN_SOURCE).unwrap();
+    // The host's lockfile, so both builds resolve the same dependency versions
+    fs::copy(Path::new(host).join("Cargo.lock"), dir.join("Cargo.lock")).unwrap();
+
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .args(["build", "--quiet", "--manifest-path"])
+        .arg(dir.join("Cargo.toml"))
+        .env_remove("CARGO_TARGET_DIR")
+        .status()
+        .unwrap();
+    assert!(status.success(), "building the plugin failed");
+
+    let name = format!(
+        "{}todo_plugin{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    );
+    dir.join("target/debug").join(name)
+}
+
+#[test]
+fn plugin_rule_runs_in_a_scan() {
+    let plugin = Plugin::load(&build_plugin()).unwrap();
+    let ids: Vec<&str> = plugin.rules().iter().map(|rule| rule.id()).collect();
+    assert_eq!(ids, ["todo-comment"]);
+
+    let scanner = Scanner::builder().plugin(plugin).build();
+    let report = scanner.scan_source("fn f() {}\n// TODO: access control\n");
+    let found: Vec<(&str, usize)> = report
+        .plugin_warnings
+        .iter()
+        .map(|finding| (finding.rule_id.as_str(), finding.line_start))
+        .collect();
+    assert_eq!(found, [("todo-comment", 2)]);
+}